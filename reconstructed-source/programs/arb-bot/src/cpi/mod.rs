@@ -6,32 +6,38 @@
 // authority is a direct signer, not a PDA.
 
 pub mod system;
+pub mod token;
 
 // CPI architecture (from Ghidra function analysis):
 //
 // 1. FUN_ram_00005620 (1,792B) — Dedicated Meteora DLMM handler
-//    Unique: variable bin array accounts (21-23 total), Swap2 instruction
+//    Unique: variable bin array accounts (17 + N, N from hop data), Swap2 instruction
 //
 // 2. FUN_ram_00005fe8 (2,904B) — Shared 10-account token-swap handler
-//    Serves: Orca Token Swap V2, Fluxbeam, Saber, Saros, StepN/Dooar, Penguin/Phoenix
+//    Serves: Orca Token Swap V1 (DjVE6) and V2, Fluxbeam, Saber, Saros,
+//    StepN/Dooar, Penguin/Phoenix
 //    All use the standard spl-token-swap interface layout
 //
-// 3. Orca Whirlpool handler (~11 accounts)
-//    Serves: Orca Whirlpool (current) + Orca Whirlpool (legacy/DjVE6)
+// 3. Orca Whirlpool handler (12 accounts, swap)
 //    Includes tick_array accounts for concentrated liquidity
 //
-// 4. Raydium CLMM handler (~13 accounts)
+// 4. FUN_ram_00007858 — Raydium CLMM handler (14 accounts + optional tick-array tail, swap_v2)
 //    Serves: Raydium CLMM, CLMM-openbook, CAMM V2 (all share the interface)
+//    plus the Raydium CLMM forks PancakeSwap and Byreal CLMM
 //
-// 5. Raydium AMM V4 handler (~17 accounts)
+// 5. Raydium AMM V4 handler (19 accounts, SwapBaseIn)
 //    Includes OpenBook/Serum market accounts (bids, asks, event_queue, vaults)
 //
-// 6. Raydium CPMM handler (~16 accounts)
+// 6. Raydium CPMM handler (14 accounts, swap_base_input / swap_base_output)
 //    Serves: Raydium CPMM + Raydium CP-AMM new
 //
-// 7. Individual handlers for: Pump.fun AMM (15), PancakeSwap (16),
-//    Byreal CLMM (12), Futarchy AMM (15), Fusion AMM (15),
-//    Meteora Dynamic AMM (15)
+// 7. Individual handlers for: Pump.fun AMM (22 sell / 24 buy),
+//    Futarchy AMM (10, spot_swap — FUN_ram_00007c80),
+//    Fusion AMM (15, Whirlpool-style swap — FUN_ram_00006b48),
+//    Meteora Dynamic AMM (16)
+//
+//    FUN_ram_00006e00 (9 accounts, byte 0x01) matches none of the integrated
+//    program ids' swap layouts and is left unattributed.
 //
 // All handlers:
 //   - Build AccountMeta vectors from remaining_accounts slice
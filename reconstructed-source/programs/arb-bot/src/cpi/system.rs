@@ -24,6 +24,9 @@ pub fn transfer_sol<'info>(
     system_program: &AccountInfo<'info>,
 ) -> Result<()> {
     msg!("tip amount: {}", lamports);
+    if lamports == 0 {
+        return Ok(());
+    }
 
     let ix = system_instruction::transfer(from.key, to.key, lamports);
     invoke(
@@ -0,0 +1,66 @@
+// =============================================================================
+// Reconstructed: programs/arb-bot/src/cpi/token.rs
+// =============================================================================
+// CPI calls to the token programs (SPL Token / Token-2022):
+//   - transfer_checked (for tips paid in the profit token)
+//     Ghidra: FUN_ram_0000cde0 (spl_token_transfer_checked)
+// =============================================================================
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+
+/// Token program `TransferChecked` instruction tag (same on SPL Token and
+/// Token-2022).
+const TOKEN_IX_TRANSFER_CHECKED: u8 = 12;
+
+/// Transfer tokens from an authority-owned account to a tip/fee account.
+///
+/// Uses TransferChecked rather than Transfer, since Token-2022 rejects the
+/// unchecked form for mints with transfer-fee or transfer-hook extensions.
+/// The instruction is built by hand so the same encoding targets either
+/// token program; `token_program` must own both token accounts and the mint.
+pub fn transfer_tokens_checked<'info>(
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    msg!("tip amount: {}", amount);
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let mut data = Vec::with_capacity(10);
+    data.push(TOKEN_IX_TRANSFER_CHECKED);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+
+    let ix = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*from.key, false),
+            AccountMeta::new_readonly(*mint.key, false),
+            AccountMeta::new(*to.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+        data,
+    };
+    invoke(
+        &ix,
+        &[
+            from.clone(),
+            mint.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
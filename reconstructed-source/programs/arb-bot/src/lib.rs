@@ -19,99 +19,350 @@
 //
 // Dispatch: Custom single-byte instruction index (NOT Anchor 8-byte discriminators)
 //   0x00 = arb_swap, 0x02 = create_token_account
+//   (0x01 / 0x03 / 0x04 = config admin, added in this reconstruction)
 //
 // Integrated DEXes (21 total):
 //   Meteora DLMM, Meteora Dynamic AMM, Raydium AMM V4, Raydium CLMM,
 //   Raydium CLMM-openbook, Raydium CAMM V2, Raydium CPMM, Raydium CP-AMM new,
-//   Orca Whirlpool, Orca Whirlpool Legacy, Orca Token Swap V2,
+//   Orca Whirlpool, Orca Token Swap V1, Orca Token Swap V2,
 //   Pump.fun AMM, PancakeSwap, Byreal CLMM, Futarchy AMM, Fusion AMM,
 //   Fluxbeam, Saber Stable Swap, Saros Swap, StepN/Dooar, Penguin/Phoenix
 // =============================================================================
 
+use std::collections::BTreeSet;
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
 
 pub mod cpi;
+pub mod extension;
 pub mod processor;
 pub mod state;
 
 declare_id!("G2E4eoenFMirpFKRfHNo2koDP7mrhnNWVwxyHASSpjQD");
 
-#[program]
-pub mod arb_bot {
-    use super::*;
-
-    /// Instruction 0x00: Execute a multi-hop arbitrage swap.
-    ///
-    /// Custom dispatch (NOT Anchor): single-byte instruction index.
-    ///
-    /// Data format (from Ghidra FUN_ram_0000e1c0 — instruction deserializer):
-    ///   [0]    : instruction index (0x00)
-    ///   [1]    : num_hops (u8)
-    ///   [2..10]: amount (u64 LE) — input amount in smallest token unit
-    ///   [10..12]: flags (u16 LE) — low byte = tip percentage (0-255)
-    ///   [12..N]: nibble-packed hop types (4-bit each, 2 per byte)
-    ///
-    /// Hop types (27 total, 0x00-0x1a) are expanded by FUN_ram_00010b78:
-    ///   Some compound types expand to pairs of atomic swaps:
-    ///     0x09 → [0x0d, 0x07], 0x0e → [0x0f, 0x05], etc.
-    ///
-    /// After all hops execute, checks profit and sends a tip:
-    ///   tip = profit * (flags & 0xFF) / 100, minimum 1000 lamports
-    pub fn arb_swap<'info>(
-        ctx: Context<'_, '_, 'info, 'info, ArbSwap<'info>>,
-        num_hops: u8,
-        amount: u64,
-        flags: u16,
-    ) -> Result<()> {
-        processor::process_arb_swap(ctx, num_hops, amount, flags)
+// =============================================================================
+// Entrypoint: custom single-byte instruction dispatch
+// =============================================================================
+// The deployed binary does not use Anchor's 8-byte discriminators, so there is
+// no #[program] module here. The entrypoint reads data[0] and routes directly:
+//   0x00 → arb_swap
+//   0x01 → initialize_config   (not in the original binary)
+//   0x02 → create_token_account
+//   0x03 → update_config       (not in the original binary)
+//   0x04 → set_admin           (not in the original binary)
+// Account validation still goes through the #[derive(Accounts)] structs below.
+
+/// Instruction index for arb_swap.
+pub const IX_ARB_SWAP: u8 = 0x00;
+
+/// Instruction index for initialize_config.
+pub const IX_INITIALIZE_CONFIG: u8 = 0x01;
+
+/// Instruction index for create_token_account.
+pub const IX_CREATE_TOKEN_ACCOUNT: u8 = 0x02;
+
+/// Instruction index for update_config.
+pub const IX_UPDATE_CONFIG: u8 = 0x03;
+
+/// Instruction index for set_admin.
+pub const IX_SET_ADMIN: u8 = 0x04;
+
+#[cfg(not(feature = "no-entrypoint"))]
+anchor_lang::solana_program::entrypoint!(process_instruction);
+
+/// Program entrypoint.
+///
+/// Ghidra source: entrypoint @ 0x00008768 (16,008 bytes, failed to decompile).
+/// From the call graph: deserialize accounts (FUN_ram_00000c60), read the
+/// instruction index byte, then either deserialize the swap route
+/// (FUN_ram_0000e1c0) and execute it, or create a token account.
+pub fn process_instruction<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    dispatch(program_id, accounts, data).map_err(|e| {
+        e.log();
+        e.into()
+    })
+}
+
+fn dispatch<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    if *program_id != ID {
+        return Err(anchor_lang::error::ErrorCode::DeclaredProgramIdMismatch.into());
     }
 
-    /// Instruction 0x02: Create a token account with a deterministic seed.
-    ///
-    /// Uses createAccountWithSeed + initializeAccount3 via CPI.
-    /// Seed is a 32-char hex string derived from the mint address.
-    /// This allows the bot to predict token account addresses off-chain.
-    ///
-    /// Ghidra (FUN_ram_00001ff0): also handles conditional tip transfer
-    /// when payer matches authority (+ 0xdab88 lamport adjustment).
-    pub fn create_token_account(ctx: Context<CreateTokenAccount>) -> Result<()> {
-        processor::process_create_token_account(ctx)
+    match data.first() {
+        Some(&IX_ARB_SWAP) => arb_swap(program_id, accounts, data),
+        Some(&IX_INITIALIZE_CONFIG) => initialize_config(program_id, accounts, data),
+        Some(&IX_CREATE_TOKEN_ACCOUNT) => create_token_account(program_id, accounts, data),
+        Some(&IX_UPDATE_CONFIG) => update_config(program_id, accounts, data),
+        Some(&IX_SET_ADMIN) => set_admin(program_id, accounts, data),
+        _ => Err(ArbBotError::InvalidInstructionData.into()),
     }
 }
 
+/// Instruction 0x00: Execute a multi-hop arbitrage swap.
+///
+/// Data format (from Ghidra FUN_ram_0000e1c0 — instruction deserializer):
+///   [0]    : instruction index (0x00)
+///   [1]    : num_hops (u8)
+///   [2..10]: amount (u64 LE) — input amount in smallest token unit
+///   [10..12]: flags (u16 LE) — versioned bitfield:
+///             low byte = tip percentage (v0, 0-100) or tip mode
+///             (v1: 0x01 = SPL transfer of the profit token, 0x02 = priced,
+///             0x04 = extra account bytes present),
+///             0x0100 = per-hop min_out present, 0x0200 = min_profit present,
+///             0x0400 = min_profit in bps of amount, 0x0800 = skip tip,
+///             bits 12..16 = version (0 or 1)
+///   [12..N]: nibble-packed hop types (4-bit each, 2 per byte)
+///   [N..M]: v1 with 0x04 only: one byte per expanded hop — extra accounts
+///           (DLMM bin arrays); absent = zero for every hop, so routes
+///           with a DLMM hop need v1 + 0x04
+///   [M..]: optional u64 min_out per expanded hop, checked after each hop
+///   [..]: optional u64 min_profit, required after the tip is subtracted
+///   [..]: v1 only: u16 tip_bps (0-10000)
+///   [..]: v1 priced tip only: u64 lamports per 1e9 profit token units
+///
+/// This format deliberately differs from the deployed program's. The only
+/// recorded on-chain arb_swap (G2E4_program_analysis.json) is the bare 12-byte
+/// header `00 02 4e94af0a00000000 4b00` with no hop type tail; the binary works
+/// the route out from the accounts, which is not recovered here. This build
+/// requires the hop type tail, so deployed-format data is rejected with
+/// InvalidInstructionData and has to be re-encoded.
+///
+/// Hop types (27 total, 0x00-0x1a) are expanded by FUN_ram_00010b78:
+///   0x09 → [0x0d, 0x07], 0x0e → [0x0f], 0x1a → [0x1b] (no known DEX, so
+///   the route fails with InvalidHopConfig). 0x01 stays atomic.
+///
+/// After all hops execute, checks profit and sends a tip:
+///   tip = profit * tip_bps / 10000, minimum Config::min_tip_lamports
+///   (1000 in the binary, converted at the tip price for priced tips; token
+///   tips have no price and no floor), capped at profit
+///   (none with the skip-tip bit), paid in lamports for WSOL cycles and
+///   as a token transfer or price-converted lamports otherwise;
+///   rejects with NotProfitable unless profit - tip >= min_profit
+fn arb_swap<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    let args = processor::decode_arb_swap_data(data)?;
+
+    let mut remaining_accounts = accounts;
+    let mut bumps = ArbSwapBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut accounts = ArbSwap::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        data,
+        &mut bumps,
+        &mut reallocs,
+    )?;
+
+    processor::process_arb_swap(
+        Context::new(program_id, &mut accounts, remaining_accounts, bumps),
+        args,
+    )?;
+
+    accounts.exit(program_id)
+}
+
+/// Instruction 0x02: Create a token account with a deterministic seed.
+///
+/// Uses createAccountWithSeed + initializeAccount3 via CPI.
+/// Seed is a 32-char hex string derived from the mint address.
+/// This allows the bot to predict token account addresses off-chain.
+///
+/// Ghidra (FUN_ram_00001ff0): also handles conditional tip transfer
+/// when payer matches authority (+ 0xdab88 lamport adjustment).
+fn create_token_account<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    if data.len() != 1 {
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+
+    let mut remaining_accounts = accounts;
+    let mut bumps = CreateTokenAccountBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut accounts = CreateTokenAccount::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        data,
+        &mut bumps,
+        &mut reallocs,
+    )?;
+
+    processor::process_create_token_account(Context::new(
+        program_id,
+        &mut accounts,
+        remaining_accounts,
+        bumps,
+    ))?;
+
+    accounts.exit(program_id)
+}
+
+/// Instruction 0x01: Create the Config PDA (not in the original binary).
+///
+/// Data: [0] = 0x01, [1..] = Borsh `state::ConfigParams`.
+/// Signed by the upgrade authority, which becomes the config admin.
+fn initialize_config<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    let params = state::ConfigParams::unpack(data)?;
+
+    let mut remaining_accounts = accounts;
+    let mut bumps = InitializeConfigBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut accounts = InitializeConfig::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        data,
+        &mut bumps,
+        &mut reallocs,
+    )?;
+
+    processor::process_initialize_config(
+        Context::new(program_id, &mut accounts, remaining_accounts, bumps),
+        params,
+    )?;
+
+    accounts.exit(program_id)
+}
+
+/// Instruction 0x03: Replace the Config settings (not in the original binary).
+///
+/// Data: [0] = 0x03, [1..] = Borsh `state::ConfigParams`.
+fn update_config<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    let params = state::ConfigParams::unpack(data)?;
+
+    let mut remaining_accounts = accounts;
+    let mut bumps = UpdateConfigBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut accounts = UpdateConfig::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        data,
+        &mut bumps,
+        &mut reallocs,
+    )?;
+
+    processor::process_update_config(
+        Context::new(program_id, &mut accounts, remaining_accounts, bumps),
+        params,
+    )?;
+
+    accounts.exit(program_id)
+}
+
+/// Instruction 0x04: Transfer the Config admin (not in the original binary).
+///
+/// Data: [0] = 0x04, [1..33] = new admin pubkey.
+fn set_admin<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    let new_admin = data
+        .get(1..)
+        .and_then(|key| Pubkey::try_from(key).ok())
+        .ok_or(ArbBotError::InvalidInstructionData)?;
+
+    let mut remaining_accounts = accounts;
+    let mut bumps = UpdateConfigBumps::default();
+    let mut reallocs = BTreeSet::new();
+    let mut accounts = UpdateConfig::try_accounts(
+        program_id,
+        &mut remaining_accounts,
+        data,
+        &mut bumps,
+        &mut reallocs,
+    )?;
+
+    processor::process_set_admin(
+        Context::new(program_id, &mut accounts, remaining_accounts, bumps),
+        new_admin,
+    )?;
+
+    accounts.exit(program_id)
+}
+
 // =============================================================================
 // Instruction 0x00: ArbSwap accounts
 // =============================================================================
 // remaining_accounts carry all DEX-specific accounts for each hop.
-// The swap_router (FUN_ram_000026c0) reads the DEX program ID from
-// remaining_accounts and dispatches to the correct CPI handler.
+// The swap_router (FUN_ram_000026c0) picks the CPI handler from each hop's
+// type; validate_route first checks every hop's first account against that
+// DEX's program ID.
 
 #[derive(Accounts)]
 pub struct ArbSwap<'info> {
-    /// The bot authority / payer. Must be the upgrade authority.
+    /// The bot authority / payer. Must be the upgrade authority or a listed
+    /// operator (checked at the start of processor::process_arb_swap).
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// This program's ProgramData account (5sR7AMdD..., from
+    /// G2E4_program_analysis.json), read for the upgrade authority.
+    #[account(address = processor::PROGRAM_DATA)]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// Runtime settings (operators, min tip, tip recipients, enabled DEXes).
+    /// Required — created once with initialize_config.
+    #[account(seeds = [state::CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, state::Config>,
+
     /// The bot's source token account (input token).
     /// The swap_executor reads balance before/after to determine profit.
     /// If mint == WSOL (So111...), reads lamports directly instead.
-    /// CHECK: Validated in processor via token account layout reads.
+    /// CHECK: Validated in processor: token-program owned, owner == authority,
+    /// not frozen.
     #[account(mut)]
     pub source_token_account: UncheckedAccount<'info>,
 
     /// The bot's destination token account (output token).
     /// May be the same as source for circular arbs (SOL→X→Y→SOL).
-    /// CHECK: Validated in processor.
+    /// CHECK: Validated in processor, same checks as source_token_account.
     #[account(mut)]
     pub destination_token_account: UncheckedAccount<'info>,
 
+    /// Tip recipient: one of the Jito tip accounts, or for token-mode tips a
+    /// token account owned by one. Optional — omit when the tip is skipped.
+    /// CHECK: Checked against processor::JITO_TIP_ACCOUNTS before the transfer.
+    #[account(mut)]
+    pub tip_recipient: Option<UncheckedAccount<'info>>,
+
+    /// System program for lamport tips.
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Token program of the source account, for token-mode tips.
+    pub token_program: Option<Interface<'info, anchor_spl::token_interface::TokenInterface>>,
+
+    /// Mint of the source account, for token-mode tips (TransferChecked).
+    /// CHECK: Must match the source account's mint; checked in processor.
+    pub tip_mint: Option<UncheckedAccount<'info>>,
+
     // remaining_accounts layout (variable, per hop):
     //   For each hop: [dex_program, pool_accounts..., token_accounts..., signer]
-    //   Account count varies: 10 (token-swap DEXes), 11 (Orca), 13 (Raydium CLMM),
-    //   15 (Pump.fun/Meteora Pools), 16 (CPMM/PancakeSwap), 17 (Raydium V4),
-    //   21-23 (Meteora DLMM with variable bin arrays)
-    //
-    // Last 2 accounts: system_program + tip_recipient (for tip transfer)
+    //   Account count varies: 10 (Futarchy), 11 (token-swap DEXes), 12 (Orca Whirlpool),
+    //   14 + N (Raydium CLMM and forks), 14 (CPMM), 15 (Fusion), 16 (Meteora Pools),
+    //   19 (Raydium V4), 22/24 (Pump.fun sell/buy),
+    //   17 + N (Meteora DLMM, N bin arrays given per hop in instruction data)
 }
 
 // =============================================================================
@@ -140,6 +391,54 @@ pub struct CreateTokenAccount<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// =============================================================================
+// Instructions 0x01 / 0x03 / 0x04: Config accounts
+// =============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// The upgrade authority; pays for the config and becomes its admin.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// This program's ProgramData account, read for the upgrade authority.
+    #[account(
+        address = processor::PROGRAM_DATA,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ArbBotError::UnauthorizedAdmin,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// The Config PDA, seeds = [b"config"].
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + state::Config::INIT_SPACE,
+        seeds = [state::CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, state::Config>,
+
+    /// System program for account creation.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for update_config (0x03) and set_admin (0x04).
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The current config admin.
+    pub admin: Signer<'info>,
+
+    /// The Config PDA, seeds = [b"config"].
+    #[account(
+        mut,
+        seeds = [state::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ArbBotError::UnauthorizedAdmin,
+    )]
+    pub config: Account<'info, state::Config>,
+}
+
 // =============================================================================
 // Custom errors (from Ghidra return code analysis)
 // =============================================================================
@@ -197,4 +496,21 @@ pub enum ArbBotError {
     ///   MetadataPointer, GroupMemberPointer, GroupPointer
     #[msg("Token constraint violation")]
     TokenConstraintViolation,
+
+    /// Not in the original binary — tip account missing or not allowlisted
+    #[msg("Invalid tip account")]
+    InvalidTipAccount,
+
+    /// Not in the original binary — arb_swap signer is not an operator
+    #[msg("Unauthorized operator")]
+    UnauthorizedOperator,
+
+    /// Not in the original binary — config signer is not the admin
+    /// (upgrade authority for initialize_config)
+    #[msg("Unauthorized admin")]
+    UnauthorizedAdmin,
+
+    /// Not in the original binary — config lists too long or unknown hop types
+    #[msg("Invalid config")]
+    InvalidConfig,
 }
@@ -5,7 +5,7 @@
 // Key functions mapped:
 //   FUN_ram_00002368 (856B)  → execute_swap_and_tip() — profit check + tip calc
 //   FUN_ram_000026c0 (11.6KB) → swap_router()         — core hop dispatch loop
-//   FUN_ram_00005620 (1.8KB) → meteora_dlmm_cpi()    — 21-23 account CPI
+//   FUN_ram_00005620 (1.8KB) → meteora_dlmm_cpi()    — 17 + bin arrays CPI
 //   FUN_ram_00005fe8 (2.9KB) → multi_dex_cpi_10()    — 10-account shared CPI
 //   FUN_ram_0000e1c0 (5KB)   → deserialize_ix_data() — instruction parser
 //   FUN_ram_00010b78 (3.4KB) → expand_hop_types()    — compound→atomic
@@ -20,12 +20,17 @@
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
+    instruction::Instruction,
     program::invoke_signed,
     program::invoke,
     system_instruction,
 };
 
-use crate::{ArbSwap, CreateTokenAccount, ArbBotError};
+use crate::{ArbSwap, CreateTokenAccount, InitializeConfig, UpdateConfig, ArbBotError};
+use crate::extension::{get_token_program_for_mint, is_token_2022};
+use crate::state::{
+    AccountCursor, ArbSwapArgs, Config, ConfigParams, HopConfig, TokenAccountState,
+};
 
 // =============================================================================
 // Constants: All 21 DEX program IDs (from .rodata section, confirmed on Solscan)
@@ -45,7 +50,7 @@ const RAYDIUM_CPAMM_NEW: Pubkey   = pubkey!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd
 
 // --- Orca ---
 const ORCA_WHIRLPOOL: Pubkey      = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
-const ORCA_WHIRLPOOL_LEGACY: Pubkey = pubkey!("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1");
+const ORCA_TOKEN_SWAP_V1: Pubkey  = pubkey!("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1");
 const ORCA_TOKEN_SWAP_V2: Pubkey  = pubkey!("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP");
 
 // --- Pump.fun ---
@@ -66,24 +71,634 @@ const FUSION_AMM: Pubkey          = pubkey!("fUSioN9YKKSa3CUC2YUc4tPkHJ5Y6XW1yz8
 const WSOL_MINT: Pubkey           = pubkey!("So11111111111111111111111111111111111111112");
 const PUMPFUN_FEE: Pubkey         = pubkey!("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ");
 
-/// Minimum tip in lamports (from Ghidra: hardcoded 1000 check in FUN_ram_00002368)
-const MIN_TIP_LAMPORTS: u64 = 1000;
+/// ProgramData account of this program (G2E4_program_analysis.json); its
+/// upgrade authority is HvFdDWS3RqymRAVx1ZdoL2RjiC38r5dtt19Z8op5jqDK.
+/// This is the upgradeable loader's PDA of `crate::ID`, pinned by a test.
+pub const PROGRAM_DATA: Pubkey = pubkey!("5sR7AMdDD779jDmDBLZrDd1tUZ8QzPpWANxnKhMZpGqC");
+
+/// Allowed tip recipients: the eight Jito tip payment accounts (used when the
+/// Config's tip recipient list is empty).
+pub const JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
 
 // =============================================================================
 // Hop type encoding (from FUN_ram_00010b78 — hop expander)
 // =============================================================================
 // 27 hop types (0x00–0x1a), nibble-packed (4-bit encoding, 2 hops per byte).
-// Some are compound types that expand to pairs of atomic swaps:
+// Some are compound types that the expander rewrites before dispatch:
 //   0x09 → [0x0d, 0x07]
-//   0x0e → [0x0f, 0x05]
-//   (and more compound mappings in the hop expander)
+//   0x0e → [0x0f]
+//   0x1a → [0x1b]
+// 0x1b only exists as an expansion result. Its DEX is not identified, so it
+// has no registry row and a route using 0x1a fails with InvalidHopConfig at
+// dispatch.
 //
-// Atomic hop types map to DEX + account layout combinations.
+// The decompiled expander also writes a single 0x02 for 0x01. Followed
+// literally, Meteora Dynamic AMM (0x01) would be unreachable, so this build
+// keeps 0x01 atomic.
+//
+// Atomic hop types map to DEX + account layout combinations (the numbering
+// is this reconstruction's convention, not recovered from the binary).
 // The swap_router dispatches based on hop_type to determine:
 //   1. Which DEX program ID to expect
 //   2. How many accounts to consume from remaining_accounts
 //   3. Which CPI handler to call
 
+/// Nibble value that escapes into the upper hop type range (0x0f..0x1a).
+/// A trailing escape nibble with nothing after it is byte padding.
+/// This encoding is this reconstruction's convention.
+const HOP_TYPE_ESCAPE: u8 = 0x0F;
+
+/// Highest valid hop type (27 types: 0x00..=0x1a).
+const MAX_HOP_TYPE: u8 = 0x1A;
+
+/// Highest hop type after expansion (0x1a → 0x1b).
+const MAX_EXPANDED_HOP_TYPE: u8 = 0x1B;
+
+/// Enabled-DEX mask with every hop type set.
+pub const ALL_HOP_TYPES: u32 = (1 << (MAX_HOP_TYPE + 1)) - 1;
+
+/// Swap direction implied by a hop type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// Detected at CPI time from the user token accounts vs the pool vaults
+    FromAccounts,
+    /// Base → quote (Pump.fun sell)
+    BaseToQuote,
+    /// Quote → base (Pump.fun buy)
+    QuoteToBase,
+}
+
+/// How the tip is paid out of the cycle's profit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TipMode {
+    /// Lamports via system transfer — only valid for WSOL cycles
+    Lamports,
+    /// SPL transfer of the profit token to the tip recipient's token account
+    Token,
+    /// Lamports via system transfer, converted from profit-token units
+    Priced { lamports_per_token_e9: u64 },
+}
+
+/// Hop types 0x00..=0x1a.
+///
+/// The type → DEX numbering is this reconstruction's own convention; the
+/// binary analysis does not give it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HopType {
+    MeteoraDlmm = 0x00,
+    MeteoraPools = 0x01,
+    RaydiumAmmV4 = 0x02,
+    RaydiumClmm = 0x03,
+    RaydiumClmmOpenbook = 0x04,
+    RaydiumCammV2 = 0x05,
+    RaydiumCpmm = 0x06,
+    RaydiumCpammNew = 0x07,
+    OrcaWhirlpool = 0x08,
+    /// Compound: Pump.fun buy → Raydium CP-AMM new
+    PumpfunBuyThenCpamm = 0x09,
+    OrcaTokenSwapV1 = 0x0a,
+    OrcaTokenSwapV2 = 0x0b,
+    PumpfunSell = 0x0c,
+    PumpfunBuy = 0x0d,
+    /// Compound: expands to a single PancakeSwap hop (0x0f)
+    PancakeSwapCompound = 0x0e,
+    PancakeSwap = 0x0f,
+    ByrealClmm = 0x10,
+    FutarchyAmm = 0x11,
+    FusionAmm = 0x12,
+    Fluxbeam = 0x13,
+    SaberStable = 0x14,
+    SarosSwap = 0x15,
+    StepnDooar = 0x16,
+    PenguinPhoenix = 0x17,
+    /// Raydium CPMM, swap_base_output
+    RaydiumCpmmExactOut = 0x18,
+    /// Raydium CP-AMM new, swap_base_output
+    RaydiumCpammNewExactOut = 0x19,
+    /// Compound: expands to 0x1b
+    ExpandsTo1b = 0x1a,
+    /// Expansion-only type (from 0x1a); its DEX is not identified, so it has
+    /// no registry row
+    Unmapped1b = 0x1b,
+}
+
+impl TryFrom<u8> for HopType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        use HopType::*;
+        Ok(match value {
+            0x00 => MeteoraDlmm,
+            0x01 => MeteoraPools,
+            0x02 => RaydiumAmmV4,
+            0x03 => RaydiumClmm,
+            0x04 => RaydiumClmmOpenbook,
+            0x05 => RaydiumCammV2,
+            0x06 => RaydiumCpmm,
+            0x07 => RaydiumCpammNew,
+            0x08 => OrcaWhirlpool,
+            0x09 => PumpfunBuyThenCpamm,
+            0x0a => OrcaTokenSwapV1,
+            0x0b => OrcaTokenSwapV2,
+            0x0c => PumpfunSell,
+            0x0d => PumpfunBuy,
+            0x0e => PancakeSwapCompound,
+            0x0f => PancakeSwap,
+            0x10 => ByrealClmm,
+            0x11 => FutarchyAmm,
+            0x12 => FusionAmm,
+            0x13 => Fluxbeam,
+            0x14 => SaberStable,
+            0x15 => SarosSwap,
+            0x16 => StepnDooar,
+            0x17 => PenguinPhoenix,
+            0x18 => RaydiumCpmmExactOut,
+            0x19 => RaydiumCpammNewExactOut,
+            0x1a => ExpandsTo1b,
+            _ => return Err(ArbBotError::InvalidHopConfig.into()),
+        })
+    }
+}
+
+impl HopType {
+    /// Hops a compound type expands to (FUN_ram_00010b78), or `None` for
+    /// atomic types.
+    pub fn expansion(self) -> Option<&'static [HopType]> {
+        match self {
+            HopType::PumpfunBuyThenCpamm => Some(&[HopType::PumpfunBuy, HopType::RaydiumCpammNew]),
+            HopType::PancakeSwapCompound => Some(&[HopType::PancakeSwap]),
+            HopType::ExpandsTo1b => Some(&[HopType::Unmapped1b]),
+            _ => None,
+        }
+    }
+
+    /// Registry row for an atomic hop type (`InvalidHopConfig` for compounds).
+    pub fn dex(self) -> Result<&'static DexEntry> {
+        DEX_REGISTRY[self as usize]
+            .as_ref()
+            .ok_or_else(|| ArbBotError::InvalidHopConfig.into())
+    }
+
+    /// Whether the hop swaps for an exact output amount.
+    pub fn is_exact_out(self) -> bool {
+        matches!(self, HopType::RaydiumCpmmExactOut | HopType::RaydiumCpammNewExactOut)
+    }
+
+    /// Direction fixed by the hop type, if any.
+    pub fn direction(self) -> SwapDirection {
+        match self {
+            HopType::PumpfunSell => SwapDirection::BaseToQuote,
+            HopType::PumpfunBuy => SwapDirection::QuoteToBase,
+            _ => SwapDirection::FromAccounts,
+        }
+    }
+}
+
+// =============================================================================
+// DEX registry (replaces the FUN_ram_000026c0 memcmp chain)
+// =============================================================================
+// One row per atomic hop type, indexed by the hop type byte. Compound types
+// (0x09, 0x0e, 0x1a) have no row: they are expanded before dispatch. 0x1b
+// has no row either, since its DEX is not identified.
+// Adding a venue = a HopType variant + one row here + its CPI handler.
+
+/// CPI handler signature shared by every DEX.
+pub type SwapHandler =
+    for<'a, 'info> fn(&Signer<'info>, &mut AccountCursor<'a, 'info>, &HopConfig) -> Result<()>;
+
+/// How many remaining_accounts a hop consumes (including the program).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountCount {
+    /// Always exactly this many accounts
+    Fixed(usize),
+    /// `base` fixed accounts followed by up to `max_extra` tail accounts
+    Variable { base: usize, max_extra: usize },
+}
+
+impl AccountCount {
+    /// Accounts in the fixed part of the layout.
+    pub const fn base(self) -> usize {
+        match self {
+            AccountCount::Fixed(n) => n,
+            AccountCount::Variable { base, .. } => base,
+        }
+    }
+
+    /// Total accounts for a hop with `extra` tail accounts, or `None` if the
+    /// layout does not allow that many.
+    pub const fn with_extra(self, extra: usize) -> Option<usize> {
+        match self {
+            AccountCount::Fixed(n) if extra == 0 => Some(n),
+            AccountCount::Fixed(_) => None,
+            AccountCount::Variable { base, max_extra } if extra <= max_extra => Some(base + extra),
+            AccountCount::Variable { .. } => None,
+        }
+    }
+}
+
+/// Static description of one DEX venue.
+pub struct DexEntry {
+    /// Program the hop's first account must be
+    pub program_id: Pubkey,
+    /// Display name for logs
+    pub name: &'static str,
+    /// CPI handler that executes the hop
+    pub handler: SwapHandler,
+    /// Accounts consumed from remaining_accounts
+    pub accounts: AccountCount,
+    /// Bit i set → hop account i is writable in the CPI
+    pub writable_mask: u32,
+    /// Hop account indices with a known role
+    pub roles: AccountRoles,
+}
+
+/// Hop account indices the router and route validation look at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountRoles {
+    /// Pool / market state, owned by the DEX program
+    pub pool: usize,
+    /// User token account the hop spends from
+    pub user_source: usize,
+    /// User token account the hop pays into
+    pub user_destination: usize,
+    /// Pool token vaults, owned by a token program
+    pub vaults: [usize; 2],
+}
+
+/// Builds a writable mask from hop account positions.
+const fn writable(indices: &[u8]) -> u32 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < indices.len() {
+        mask |= 1 << indices[i];
+        i += 1;
+    }
+    mask
+}
+
+const fn dex(
+    program_id: Pubkey,
+    name: &'static str,
+    handler: SwapHandler,
+    accounts: AccountCount,
+    writable_mask: u32,
+    roles: AccountRoles,
+) -> Option<DexEntry> {
+    Some(DexEntry {
+        program_id,
+        name,
+        handler,
+        accounts,
+        writable_mask,
+        roles,
+    })
+}
+
+const fn roles(pool: usize, user_source: usize, user_destination: usize, vaults: [usize; 2]) -> AccountRoles {
+    AccountRoles {
+        pool,
+        user_source,
+        user_destination,
+        vaults,
+    }
+}
+
+const TOKEN_SWAP_WRITABLE: u32 = writable(&[4, 5, 6, 7, 8, 9]);
+const SABER_WRITABLE: u32 = writable(&[4, 5, 6, 7, 8]);
+const METEORA_DLMM_ACCOUNTS: AccountCount = AccountCount::Variable { base: 17, max_extra: 8 };
+const RAYDIUM_CLMM_ACCOUNTS: AccountCount = AccountCount::Variable { base: 14, max_extra: 4 };
+const RAYDIUM_CLMM_WRITABLE: u32 = writable(&[3, 4, 5, 6, 7, 8]);
+const RAYDIUM_CPMM_WRITABLE: u32 = writable(&[4, 5, 6, 7, 8, 13]);
+const ORCA_WHIRLPOOL_WRITABLE: u32 = writable(&[3, 4, 5, 6, 7, 8, 9, 10, 11]);
+const TOKEN_SWAP_ROLES: AccountRoles = roles(1, 4, 7, [5, 6]);
+const RAYDIUM_CLMM_ROLES: AccountRoles = roles(3, 4, 5, [6, 7]);
+const RAYDIUM_CPMM_ROLES: AccountRoles = roles(4, 5, 6, [7, 8]);
+const ORCA_WHIRLPOOL_ROLES: AccountRoles = roles(3, 4, 6, [5, 7]);
+const PUMPFUN_SELL_WRITABLE: u32 = writable(&[1, 2, 6, 7, 8, 9, 11, 18]);
+const PUMPFUN_BUY_WRITABLE: u32 = PUMPFUN_SELL_WRITABLE | writable(&[20, 21]);
+
+/// Indexed by `HopType as usize` (28 rows, 0x00..=0x1b).
+static DEX_REGISTRY: [Option<DexEntry>; MAX_EXPANDED_HOP_TYPE as usize + 1] = [
+    /* 0x00 */ dex(METEORA_DLMM, "Meteora DLMM", meteora_dlmm_cpi,
+                   METEORA_DLMM_ACCOUNTS,
+                   writable(&[1, 3, 4, 5, 6, 9, 10]), roles(1, 5, 6, [3, 4])),
+    /* 0x01 */ dex(METEORA_POOLS, "Meteora Dynamic AMM", meteora_pools_cpi,
+                   AccountCount::Fixed(16),
+                   writable(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]), roles(1, 2, 3, [6, 7])),
+    /* 0x02 */ dex(RAYDIUM_AMM_V4, "Raydium AMM V4", raydium_amm_v4_cpi,
+                   AccountCount::Fixed(19),
+                   writable(&[1, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 15, 16]),
+                   roles(1, 15, 16, [5, 6])),
+    /* 0x03 */ dex(RAYDIUM_CLMM, "Raydium CLMM", raydium_clmm_cpi,
+                   RAYDIUM_CLMM_ACCOUNTS, RAYDIUM_CLMM_WRITABLE, RAYDIUM_CLMM_ROLES),
+    /* 0x04 */ dex(RAYDIUM_CLMM_OPENBOOK, "Raydium CLMM (openbook)", raydium_clmm_cpi,
+                   RAYDIUM_CLMM_ACCOUNTS, RAYDIUM_CLMM_WRITABLE, RAYDIUM_CLMM_ROLES),
+    /* 0x05 */ dex(RAYDIUM_CAMM_V2, "Raydium CAMM V2", raydium_clmm_cpi,
+                   RAYDIUM_CLMM_ACCOUNTS, RAYDIUM_CLMM_WRITABLE, RAYDIUM_CLMM_ROLES),
+    /* 0x06 */ dex(RAYDIUM_CPMM, "Raydium CPMM", raydium_cpmm_cpi,
+                   AccountCount::Fixed(14), RAYDIUM_CPMM_WRITABLE, RAYDIUM_CPMM_ROLES),
+    /* 0x07 */ dex(RAYDIUM_CPAMM_NEW, "Raydium CP-AMM (new)", raydium_cpmm_cpi,
+                   AccountCount::Fixed(14), RAYDIUM_CPMM_WRITABLE, RAYDIUM_CPMM_ROLES),
+    /* 0x08 */ dex(ORCA_WHIRLPOOL, "Orca Whirlpool", orca_whirlpool_cpi,
+                   AccountCount::Fixed(12), ORCA_WHIRLPOOL_WRITABLE, ORCA_WHIRLPOOL_ROLES),
+    /* 0x09 */ None,
+    /* 0x0a */ dex(ORCA_TOKEN_SWAP_V1, "Orca Token Swap V1", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x0b */ dex(ORCA_TOKEN_SWAP_V2, "Orca Token Swap V2", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x0c */ dex(PUMPFUN_AMM, "Pump.fun AMM (sell)", pumpfun_amm_cpi,
+                   AccountCount::Fixed(22), PUMPFUN_SELL_WRITABLE, roles(1, 6, 7, [8, 9])),
+    /* 0x0d */ dex(PUMPFUN_AMM, "Pump.fun AMM (buy)", pumpfun_amm_cpi,
+                   AccountCount::Fixed(24), PUMPFUN_BUY_WRITABLE, roles(1, 7, 6, [8, 9])),
+    /* 0x0e */ None,
+    /* 0x0f */ dex(PANCAKESWAP, "PancakeSwap", raydium_clmm_cpi,
+                   RAYDIUM_CLMM_ACCOUNTS, RAYDIUM_CLMM_WRITABLE, RAYDIUM_CLMM_ROLES),
+    /* 0x10 */ dex(BYREAL_CLMM, "Byreal CLMM", raydium_clmm_cpi,
+                   RAYDIUM_CLMM_ACCOUNTS, RAYDIUM_CLMM_WRITABLE, RAYDIUM_CLMM_ROLES),
+    /* 0x11 */ dex(FUTARCHY_AMM, "Futarchy AMM", futarchy_amm_cpi,
+                   AccountCount::Fixed(10), writable(&[1, 2, 3, 4, 5]), roles(1, 2, 3, [4, 5])),
+    /* 0x12 */ dex(FUSION_AMM, "Fusion AMM", fusion_amm_cpi,
+                   AccountCount::Fixed(15), writable(&[5, 8, 9, 10, 11, 12, 13, 14]),
+                   roles(5, 8, 10, [9, 11])),
+    /* 0x13 */ dex(FLUXBEAM, "Fluxbeam", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x14 */ dex(SABER_STABLE, "Saber Stable Swap", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), SABER_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x15 */ dex(SAROS_SWAP, "Saros Swap", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x16 */ dex(STEPN_DOOAR, "StepN DEX (Dooar)", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x17 */ dex(PENGUIN_PHOENIX, "Penguin Swap / Phoenix", multi_dex_10_account_cpi,
+                   AccountCount::Fixed(11), TOKEN_SWAP_WRITABLE, TOKEN_SWAP_ROLES),
+    /* 0x18 */ dex(RAYDIUM_CPMM, "Raydium CPMM (exact out)", raydium_cpmm_cpi,
+                   AccountCount::Fixed(14), RAYDIUM_CPMM_WRITABLE, RAYDIUM_CPMM_ROLES),
+    /* 0x19 */ dex(RAYDIUM_CPAMM_NEW, "Raydium CP-AMM new (exact out)", raydium_cpmm_cpi,
+                   AccountCount::Fixed(14), RAYDIUM_CPMM_WRITABLE, RAYDIUM_CPMM_ROLES),
+    /* 0x1a */ None,
+    /* 0x1b */ None,
+];
+
+/// Reads one hop type starting at nibble `index`, advancing past it.
+fn read_hop_type(packed: &[u8], index: &mut usize) -> Result<u8> {
+    let nibble = |i: usize| -> Result<u8> {
+        let byte = packed
+            .get(i / 2)
+            .ok_or(ArbBotError::InvalidInstructionData)?;
+        Ok(if i & 1 == 0 { byte & 0x0F } else { byte >> 4 })
+    };
+
+    let mut hop_type = nibble(*index)?;
+    *index += 1;
+    if hop_type == HOP_TYPE_ESCAPE {
+        hop_type += nibble(*index)?;
+        *index += 1;
+    }
+    if hop_type > MAX_HOP_TYPE {
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+
+    Ok(hop_type)
+}
+
+/// Unpacks nibble-packed hop types and expands compound types into their
+/// atomic hops.
+///
+/// Ghidra source: FUN_ram_00010b78 (expand_hop_types, 3,440 bytes)
+///
+/// `packed` is the hop type tail exactly as stored in `ArbSwapArgs`; a final
+/// 0xF pad nibble is skipped.
+pub fn expand_hop_types(packed: &[u8]) -> Result<Vec<HopType>> {
+    let total_nibbles = packed.len() * 2;
+    let mut hops = Vec::with_capacity(total_nibbles);
+    let mut index = 0;
+
+    while index < total_nibbles {
+        let is_pad = index + 1 == total_nibbles && packed[index / 2] >> 4 == HOP_TYPE_ESCAPE;
+        if is_pad {
+            break;
+        }
+
+        let hop = HopType::try_from(read_hop_type(packed, &mut index)?)?;
+        match hop.expansion() {
+            Some(atomic) => hops.extend_from_slice(atomic),
+            None => hops.push(hop),
+        }
+    }
+
+    if hops.is_empty() {
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    Ok(hops)
+}
+
+// =============================================================================
+// Instruction data deserializer (reconstructed from FUN_ram_0000e1c0)
+// =============================================================================
+
+/// Length of the fixed arb_swap header: index + num_hops + amount + flags.
+const ARB_SWAP_HEADER_LEN: usize = 12;
+
+/// flags bit: a u64 min_out per expanded hop follows the hop types (and the
+/// extra account bytes, when present).
+pub const FLAG_HOP_MIN_OUT: u16 = 0x0100;
+/// flags bit: a u64 min_profit follows the min_out section.
+pub const FLAG_MIN_PROFIT: u16 = 0x0200;
+/// flags bit: min_profit is in bps of `amount` instead of absolute units.
+pub const FLAG_MIN_PROFIT_BPS: u16 = 0x0400;
+/// flags bit: pay no tip (no tip accounts needed).
+pub const FLAG_SKIP_TIP: u16 = 0x0800;
+
+/// flags bits 12..16: layout version of the flags word.
+///   v0: low byte = tip percent (the binary's format, 0..=100)
+///   v1: low byte = tip mode bits, tip_bps (u16 LE) trails the instruction data
+const FLAGS_VERSION_SHIFT: u16 = 12;
+pub const FLAGS_V0: u16 = 0;
+pub const FLAGS_V1: u16 = 1;
+
+/// flags v1 low byte: tip paid in the profit token (SPL transfer).
+pub const FLAG_V1_TIP_TOKEN: u16 = 0x0001;
+/// flags v1 low byte: tip converted to lamports at a price from the data.
+pub const FLAG_V1_TIP_PRICED: u16 = 0x0002;
+/// flags v1 low byte: one extra-account byte per expanded hop follows the hop
+/// types. Without it (and always in v0) every hop has zero extra accounts,
+/// so routes with a Meteora DLMM hop (which needs bin arrays) require v1
+/// with this flag.
+pub const FLAG_V1_EXTRA_ACCOUNTS: u16 = 0x0004;
+/// flags v1 low byte: every defined bit; the rest must be zero.
+const FLAGS_V1_LOW_BITS: u16 = FLAG_V1_TIP_TOKEN | FLAG_V1_TIP_PRICED | FLAG_V1_EXTRA_ACCOUNTS;
+
+/// Tip ceiling: 100% of profit.
+const MAX_TIP_BPS: u16 = 10_000;
+
+/// Scale of the tip price: lamports per 1e9 units of the profit token.
+const TIP_PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Decodes arb_swap instruction data (instruction 0x00).
+///
+/// Ghidra source: FUN_ram_0000e1c0 (deserialize_swap_route_config, 5KB)
+///
+/// Layout:
+///   [0]      : instruction index (0x00)
+///   [1]      : num_hops (u8, must be non-zero)
+///   [2..10]  : amount (u64 LE)
+///   [10..12] : flags (u16 LE)
+///   [12..N]  : nibble-packed hop types, low nibble first
+///   [N..M]   : flags v1 with FLAG_V1_EXTRA_ACCOUNTS: one u8 per expanded
+///              hop — variable tail account count (Meteora DLMM bin arrays;
+///              must be 0 for fixed layouts). Absent = all zero.
+///   [M..]    : if flags & FLAG_HOP_MIN_OUT, one u64 LE per expanded hop —
+///              minimum output of that hop
+///   [..]     : if flags & FLAG_MIN_PROFIT, min_profit (u64 LE) — absolute,
+///              or bps of amount with FLAG_MIN_PROFIT_BPS
+///   [..]     : flags v1 only: tip_bps (u16 LE, 0..=10000)
+///   [..]     : flags v1 with FLAG_V1_TIP_PRICED: lamports per 1e9 profit
+///              token units (u64 LE, non-zero)
+///
+/// Hop type nibbles (this reconstruction's own escape convention; the
+/// analysis only says types are nibble-packed):
+///   0x0..0xE   → hop type 0x00..0x0e
+///   0xF, n     → hop type 0x0f + n (0x0f..0x1a)
+///   0xF at end → padding for an odd nibble count
+///
+/// The hop type section must hold exactly `num_hops` hop types and the extra
+/// account section, when flagged, exactly one byte per hop after compound
+/// expansion (plus the min_out / min_profit / tip_bps sections when present):
+/// a short buffer, trailing bytes, a non-0xF pad nibble or a hop type above
+/// 0x1a are all rejected with `InvalidInstructionData`, as are an unknown
+/// flags version, unknown v1 low-byte bits, a tip above 100% and a Meteora
+/// DLMM hop without bin arrays (always the case in v0). The bare
+/// 12-byte header the deployed program accepts is rejected too (no hop types).
+pub fn decode_arb_swap_data(data: &[u8]) -> Result<ArbSwapArgs> {
+    if data.len() < ARB_SWAP_HEADER_LEN || data[0] != 0x00 {
+        msg!("Unexpected length of input: {}", data.len());
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+
+    let num_hops = data[1];
+    let amount = u64::from_le_bytes(data[2..10].try_into().unwrap());
+    let flags = u16::from_le_bytes(data[10..12].try_into().unwrap());
+
+    if num_hops == 0 {
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+
+    let tail = &data[ARB_SWAP_HEADER_LEN..];
+    let packed_len = packed_hop_types_len(tail, num_hops)?;
+    let (hop_types, tail) = tail.split_at(packed_len);
+
+    let hops = expand_hop_types(hop_types)?;
+    let expanded_hops = hops.len();
+    let min_out_len = if flags & FLAG_HOP_MIN_OUT != 0 { expanded_hops * 8 } else { 0 };
+    let min_profit_len = if flags & FLAG_MIN_PROFIT != 0 { 8 } else { 0 };
+    let extra_accounts_len = match flags >> FLAGS_VERSION_SHIFT {
+        FLAGS_V1 if flags & FLAG_V1_EXTRA_ACCOUNTS != 0 => expanded_hops,
+        _ => 0,
+    };
+    let (tip_bps_len, tip_price_len) = match flags >> FLAGS_VERSION_SHIFT {
+        FLAGS_V0 => (0, 0),
+        FLAGS_V1 if flags & 0xFF & !FLAGS_V1_LOW_BITS != 0 => {
+            return Err(ArbBotError::InvalidInstructionData.into());
+        }
+        FLAGS_V1 => match flags & (FLAG_V1_TIP_TOKEN | FLAG_V1_TIP_PRICED) {
+            0 | FLAG_V1_TIP_TOKEN => (2, 0),
+            FLAG_V1_TIP_PRICED => (2, 8),
+            _ => return Err(ArbBotError::InvalidInstructionData.into()),
+        },
+        _ => return Err(ArbBotError::InvalidInstructionData.into()),
+    };
+    if tail.len()
+        != extra_accounts_len + min_out_len + min_profit_len + tip_bps_len + tip_price_len
+    {
+        msg!("Unexpected length of input: {}", data.len());
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+    let (hop_extra_accounts, tail) = tail.split_at(extra_accounts_len);
+    let (min_outs, tail) = tail.split_at(min_out_len);
+    let (min_profit, tail) = tail.split_at(min_profit_len);
+    let (tip_bps, tip_price) = tail.split_at(tip_bps_len);
+
+    // DLMM swap2 fails without bin arrays, and v0 has no way to pass them.
+    let hop_extra_accounts = match hop_extra_accounts {
+        [] => vec![0; expanded_hops],
+        bytes => bytes.to_vec(),
+    };
+    let dlmm_without_bin_arrays = hops
+        .iter()
+        .zip(&hop_extra_accounts)
+        .any(|(hop, extra)| *hop == HopType::MeteoraDlmm && *extra == 0);
+    if dlmm_without_bin_arrays {
+        msg!("Meteora DLMM hop needs bin arrays (flags v1 + FLAG_V1_EXTRA_ACCOUNTS)");
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+
+    let tip_bps = match tip_bps {
+        [] => (flags & 0xFF) * 100,
+        bytes => u16::from_le_bytes(bytes.try_into().unwrap()),
+    };
+    if tip_bps > MAX_TIP_BPS {
+        return Err(ArbBotError::InvalidInstructionData.into());
+    }
+    let tip_mode = match tip_price {
+        [] if tip_bps_len != 0 && flags & FLAG_V1_TIP_TOKEN != 0 => TipMode::Token,
+        [] => TipMode::Lamports,
+        bytes => match u64::from_le_bytes(bytes.try_into().unwrap()) {
+            0 => return Err(ArbBotError::InvalidInstructionData.into()),
+            lamports_per_token_e9 => TipMode::Priced { lamports_per_token_e9 },
+        },
+    };
+
+    Ok(ArbSwapArgs {
+        num_hops,
+        amount,
+        flags,
+        hop_types: hop_types.to_vec(),
+        hop_extra_accounts,
+        hop_min_out: min_outs
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect(),
+        min_profit: match min_profit {
+            [] => 0,
+            bytes => u64::from_le_bytes(bytes.try_into().unwrap()),
+        },
+        tip_bps,
+        tip_mode,
+    })
+}
+
+/// Walks `count` nibble-packed hop types at the start of `data` and returns
+/// how many bytes they occupy (including a trailing pad nibble).
+fn packed_hop_types_len(data: &[u8], count: u8) -> Result<usize> {
+    let mut index = 0;
+    for _ in 0..count {
+        read_hop_type(data, &mut index)?;
+    }
+
+    // Odd nibble count: the unused high nibble must be the pad value
+    if index & 1 == 1 {
+        let pad = data
+            .get(index / 2)
+            .ok_or(ArbBotError::InvalidInstructionData)?
+            >> 4;
+        if pad != HOP_TYPE_ESCAPE {
+            return Err(ArbBotError::InvalidInstructionData.into());
+        }
+    }
+
+    Ok(index.div_ceil(2))
+}
+
 // =============================================================================
 // Main entry: process_arb_swap (reconstructed from FUN_ram_00002368)
 // =============================================================================
@@ -93,21 +708,43 @@ const MIN_TIP_LAMPORTS: u64 = 1000;
 /// Ghidra source: FUN_ram_00002368 (execute_swap_and_tip, 856 bytes)
 ///
 /// Execution flow:
-/// 1. Read initial balance of source token account (native SOL via WSOL check)
-/// 2. Call swap_router() to execute all hops
-/// 3. Read final balance after all swaps
-/// 4. Profit check: final_balance > initial_balance
-/// 5. Calculate tip: profit * tip_bps / 100, minimum 1000 lamports
-/// 6. Transfer tip via System Program transfer
-/// 7. Return 0x1771 (error 6001 = NotProfitable) or 0x1772 (success)
+/// 1. Check the operator, validate the whole route and the user token accounts
+/// 2. Read initial balance of source token account (native SOL via WSOL check)
+/// 3. Call swap_router() for each hop, chaining each hop's realized output
+/// 4. Read final balance; NotProfitable (6001) unless it grew
+/// 5. Tip (`settle_tip`): profit * tip_bps / 10000, with tip_bps from the v0
+///    percent byte or the v1 field, floored at Config::min_tip_lamports for
+///    lamport and priced tips, capped at the profit, zero with FLAG_SKIP_TIP;
+///    NotProfitable unless profit - tip reaches min_profit
+/// 6. Pay the tip to an allowed recipient: System transfer for lamport and
+///    priced tips, TransferChecked for token tips
 pub fn process_arb_swap<'info>(
     ctx: Context<'_, '_, 'info, 'info, ArbSwap<'info>>,
-    num_hops: u8,
-    amount: u64,
-    flags: u16,
+    args: ArbSwapArgs,
 ) -> Result<()> {
+    let ArbSwapArgs {
+        amount,
+        flags,
+        hop_types,
+        hop_extra_accounts,
+        hop_min_out,
+        min_profit,
+        tip_bps,
+        tip_mode,
+        ..
+    } = args;
     let authority = &ctx.accounts.authority;
-    let remaining = ctx.remaining_accounts;
+    let config = &ctx.accounts.config;
+    check_operator(authority, &ctx.accounts.program_data, config)?;
+
+    // --- Step 0: Expand and validate the route ---
+    // FUN_ram_00010b78 expands compound hops into atomic hops. The whole
+    // route is checked before the first CPI so a bad late hop costs nothing,
+    // and profit is only measured on token accounts the authority controls.
+    let hops = expand_hop_types(&hop_types)?;
+    validate_route(&hops, &hop_extra_accounts, ctx.remaining_accounts, config.enabled_dexes)?;
+    check_user_token_account(&ctx.accounts.source_token_account, authority)?;
+    check_user_token_account(&ctx.accounts.destination_token_account, authority)?;
 
     // --- Step 1: Record initial balance ---
     // FUN_ram_00002368 checks if the mint is WSOL (So111...) via memcmp.
@@ -122,19 +759,20 @@ pub fn process_arb_swap<'info>(
     };
 
     // --- Step 2: Execute all hops via swap_router ---
-    // The hop types are nibble-packed in instruction data.
-    // FUN_ram_00010b78 expands compound hops into atomic hops first.
-    let mut account_offset = 0;
-    for hop_index in 0..num_hops {
-        swap_router(
+    // Each hop spends what the previous one actually produced.
+    let mut cursor = AccountCursor::new(ctx.remaining_accounts);
+    let mut hop_amount = amount;
+    for (hop_index, (hop, extra_accounts)) in hops.into_iter().zip(hop_extra_accounts).enumerate() {
+        hop_amount = swap_router(
             authority,
-            remaining,
-            &mut account_offset,
-            amount,
-            hop_index,
+            &mut cursor,
+            hop_amount,
+            hop_min_out.get(hop_index).copied().unwrap_or(0),
+            hop,
+            extra_accounts,
         )?;
 
-        msg!("hop: {}", hop_index);
+        msg!("hop: {} out: {}", hop_index, hop_amount);
     }
 
     // --- Step 3: Read final balance ---
@@ -152,51 +790,200 @@ pub fn process_arb_swap<'info>(
 
     let profit = final_balance - initial_balance;
 
-    // --- Step 5: Calculate tip ---
-    // Ghidra: tip = profit * (flags & 0xFF) / 100
-    // The low byte of flags is the tip percentage in basis points / 100
-    // (i.e., flags & 0xFF represents a percentage 0-255%)
-    let tip_bps = (flags & 0xFF) as u64;
-    let mut tip_amount = profit
-        .checked_mul(tip_bps)
-        .unwrap_or(0)
-        .checked_div(100)
-        .unwrap_or(0);
-
-    // Minimum tip: 1000 lamports (hardcoded in binary)
-    if tip_amount < MIN_TIP_LAMPORTS {
-        tip_amount = MIN_TIP_LAMPORTS;
-    }
+    // --- Step 5: Tip and minimum profit ---
+    // Ghidra: tip = profit * (flags & 0xFF) / 100, floored at 1000 lamports.
+    // The floor is the Config's min_tip_lamports, kept in profit-token units
+    // like the tip until it is paid out. Token tips carry no lamport price to
+    // convert it with, so they are not floored.
+    let min_tip_lamports = config.min_tip_lamports;
+    let min_tip = match tip_mode {
+        TipMode::Lamports => min_tip_lamports,
+        TipMode::Token => 0,
+        TipMode::Priced { lamports_per_token_e9 } => {
+            lamports_to_tokens(min_tip_lamports, lamports_per_token_e9)?
+        }
+    };
+    let tip_amount = settle_tip(profit, amount, flags, tip_bps, min_tip, min_profit)?;
 
     msg!("tip amount: {}", tip_amount);
 
     // --- Step 6: Transfer tip ---
-    // Uses System Program Transfer to a tip account (Jito tip or similar)
-    // The tip recipient is in remaining_accounts at a known offset
-    // (typically the last account, or a PDA-derived tip address).
-    crate::cpi::system::transfer_sol(
-        &authority.to_account_info(),
-        &remaining[remaining.len() - 1], // tip recipient
-        tip_amount,
-        &remaining[remaining.len() - 2], // system program
-    )?;
+    // The binary took the tip recipient and system program from the tail
+    // of remaining_accounts; they are typed optional accounts here, and the
+    // recipient (or the owner of a token-mode recipient) must be a Jito tip
+    // account, or one of the Config's tip recipients when it lists any.
+    if tip_amount > 0 {
+        if tip_mode == TipMode::Lamports && !is_native_sol {
+            msg!("lamport tip needs a WSOL cycle");
+            return Err(ArbBotError::InvalidInstructionData.into());
+        }
+        let tip_recipient = ctx
+            .accounts
+            .tip_recipient
+            .as_ref()
+            .ok_or(ArbBotError::InvalidTipAccount)?;
+        let tip_owner = match tip_mode {
+            TipMode::Token => TokenAccountState::unpack(&tip_recipient.try_borrow_data()?)?.owner,
+            _ => *tip_recipient.key,
+        };
+        let allowed = if config.tip_recipients.is_empty() {
+            &JITO_TIP_ACCOUNTS[..]
+        } else {
+            &config.tip_recipients[..]
+        };
+        if !allowed.contains(&tip_owner) {
+            msg!("tip recipient not allowed: {}", tip_owner);
+            return Err(ArbBotError::InvalidTipAccount.into());
+        }
+
+        match tip_mode {
+            TipMode::Token => {
+                let token_program = ctx
+                    .accounts
+                    .token_program
+                    .as_ref()
+                    .ok_or(ArbBotError::InvalidTipAccount)?;
+                if token_program.key != source_account.owner {
+                    return Err(ArbBotError::InvalidAccountState.into());
+                }
+                // TransferChecked needs the mint and its decimals: Token-2022
+                // rejects plain Transfer for transfer-fee / transfer-hook mints.
+                let tip_mint = ctx
+                    .accounts
+                    .tip_mint
+                    .as_ref()
+                    .ok_or(ArbBotError::InvalidTipAccount)?;
+                let source_mint = TokenAccountState::unpack(&source_account.try_borrow_data()?)?.mint;
+                if *tip_mint.key != source_mint {
+                    return Err(ArbBotError::InvalidAccountState.into());
+                }
+                let decimals =
+                    crate::extension::get_mint_decimals(&tip_mint.try_borrow_data()?)?;
+                crate::cpi::token::transfer_tokens_checked(
+                    source_account,
+                    tip_mint,
+                    tip_recipient,
+                    &authority.to_account_info(),
+                    tip_amount,
+                    decimals,
+                    &token_program.to_account_info(),
+                )?;
+            }
+            TipMode::Lamports | TipMode::Priced { .. } => {
+                let lamports = match tip_mode {
+                    TipMode::Priced { lamports_per_token_e9 } => {
+                        tokens_to_lamports(tip_amount, lamports_per_token_e9)?
+                    }
+                    _ => tip_amount,
+                };
+                let system_program = ctx
+                    .accounts
+                    .system_program
+                    .as_ref()
+                    .ok_or(ArbBotError::InvalidTipAccount)?;
+                crate::cpi::system::transfer_sol(
+                    &authority.to_account_info(),
+                    tip_recipient,
+                    lamports,
+                    &system_program.to_account_info(),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// Route validation (runs before any CPI)
+// =============================================================================
+
+/// Checks the whole route against remaining_accounts before the first CPI,
+/// so a bad late hop fails without paying for the hops before it.
+///
+/// Per hop: program id and account count (as swap_router would see them),
+/// pool owned by the DEX program, vaults owned by a token program. Across
+/// hops: each hop's input mint must be the previous hop's output mint.
+/// A short account list is CalculationError (6003, as in the binary); every
+/// other violation is InvalidHopConfig. swap_router relies on these checks.
+fn validate_route(
+    hops: &[HopType],
+    hop_extra_accounts: &[u8],
+    remaining_accounts: &[AccountInfo],
+    enabled_dexes: u32,
+) -> Result<()> {
+    let mut cursor = AccountCursor::new(remaining_accounts);
+    let mut previous_output: Option<Pubkey> = None;
+
+    for (hop, extra_accounts) in hops.iter().zip(hop_extra_accounts) {
+        let entry = hop.dex()?;
+        if enabled_dexes & (1 << *hop as u8) == 0 {
+            msg!("{}: hop type {:?} disabled by config", entry.name, hop);
+            return Err(ArbBotError::InvalidHopConfig.into());
+        }
+        let roles = entry.roles;
+        let num_accounts = entry
+            .accounts
+            .with_extra(*extra_accounts as usize)
+            .ok_or(ArbBotError::InvalidHopConfig)?;
+        let accounts = cursor.take(num_accounts).inspect_err(|_| {
+            msg!("Failed to calculate account length: {}", entry.name);
+        })?;
+
+        if *accounts[0].key != entry.program_id {
+            msg!("{} expects program {}, got {}", entry.name, entry.program_id, accounts[0].key);
+            return Err(ArbBotError::InvalidHopConfig.into());
+        }
+        if *accounts[roles.pool].owner != entry.program_id {
+            msg!("{}: pool {} not owned by the DEX", entry.name, accounts[roles.pool].key);
+            return Err(ArbBotError::InvalidHopConfig.into());
+        }
+        for vault in roles.vaults.map(|i| &accounts[i]) {
+            if *vault.owner != spl_token::id() && !is_token_2022(vault.owner) {
+                msg!("{}: vault {} not a token account", entry.name, vault.key);
+                return Err(ArbBotError::InvalidHopConfig.into());
+            }
+        }
+
+        let input = hop_token_mint(&accounts[roles.user_source])?;
+        if previous_output.is_some_and(|mint| mint != input) {
+            msg!("{}: input mint {} does not follow the previous hop", entry.name, input);
+            return Err(ArbBotError::InvalidHopConfig.into());
+        }
+        previous_output = Some(hop_token_mint(&accounts[roles.user_destination])?);
+    }
 
     Ok(())
 }
 
+/// Mint of a hop's user token account; unreadable data is a route error.
+fn hop_token_mint(account: &AccountInfo) -> Result<Pubkey> {
+    let data = account.try_borrow_data()?;
+    TokenAccountState::unpack(&data)
+        .map(|state| state.mint)
+        .map_err(|_| ArbBotError::InvalidHopConfig.into())
+}
+
 // =============================================================================
 // Swap Router (reconstructed from FUN_ram_000026c0, 11,608 bytes)
 // =============================================================================
 
-/// Core swap dispatch: identifies DEX by program ID and routes to the correct
-/// CPI handler.
+/// Core swap dispatch: looks the hop type up in `DEX_REGISTRY` and calls the
+/// row's CPI handler. The account count and program account were already
+/// checked by `validate_route`.
+/// Returns the hop's realized output, measured as the balance delta of the
+/// row's user destination account across the CPI. `min_amount_out` is passed
+/// to the DEX as its own slippage limit and re-checked against that delta, so
+/// a route that goes bad aborts at the hop that went bad.
 ///
 /// Ghidra source: FUN_ram_000026c0 (swap_router, 11,608 bytes — largest function)
 ///
-/// Logic: For each hop, reads the DEX program ID from remaining_accounts,
-/// then chains ~20 sol_memcmp_ calls against hardcoded 32-byte pubkeys
-/// in .rodata. When a match is found, dispatches to the appropriate CPI
-/// handler with the correct number of accounts.
+/// The binary chains ~20 sol_memcmp_ calls against hardcoded 32-byte pubkeys
+/// in .rodata to find the handler. Here the hop type indexes the registry
+/// directly, and validate_route's single 32-byte comparison confirms the
+/// program, so a route whose program account disagrees with its hop type
+/// fails with InvalidHopConfig instead of being sent to whichever handler
+/// matches.
 ///
 /// Return codes (from Ghidra):
 ///   0x1771 → success (all hops profitable)
@@ -207,195 +994,267 @@ pub fn process_arb_swap<'info>(
 ///   6000   → unknown DEX program (error)
 fn swap_router<'info>(
     authority: &Signer<'info>,
-    remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
+    cursor: &mut AccountCursor<'_, 'info>,
     amount: u64,
-    _hop_index: u8,
-) -> Result<()> {
-    // Read the DEX program ID for this hop.
-    // The program ID is at a known position relative to account_offset.
-    // In the binary, it reads the key() of the account and does memcmp.
-    let dex_program = &remaining_accounts[*account_offset];
-    let dex_program_id = dex_program.key();
+    min_amount_out: u64,
+    hop: HopType,
+    extra_accounts: u8,
+) -> Result<u64> {
+    let entry = hop.dex()?;
 
-    // =========================================================================
-    // Dispatch chain (reconstructed from FUN_ram_000026c0 memcmp sequence)
-    // =========================================================================
+    // The registry plus the hop's extra account count fix how many accounts
+    // this hop consumes (validate_route already checked the list is long
+    // enough and starts with the DEX program).
+    let num_accounts = entry
+        .accounts
+        .with_extra(extra_accounts as usize)
+        .ok_or(ArbBotError::InvalidHopConfig)?;
+    let hop_accounts = cursor.remaining();
 
-    // --- Meteora DLMM (dedicated handler: FUN_ram_00005620, 21-23 accounts) ---
-    if dex_program_id == METEORA_DLMM {
-        meteora_dlmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Meteora Dynamic AMM (Pools) (~14-15 accounts) ---
-    else if dex_program_id == METEORA_POOLS {
-        meteora_pools_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium AMM V4 (~17 accounts) ---
-    else if dex_program_id == RAYDIUM_AMM_V4 {
-        raydium_amm_v4_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium CLMM (~13 accounts) ---
-    else if dex_program_id == RAYDIUM_CLMM {
-        raydium_clmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium CLMM openbook (~13 accounts) ---
-    else if dex_program_id == RAYDIUM_CLMM_OPENBOOK {
-        raydium_clmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium CAMM V2 (~13 accounts, same handler as CLMM) ---
-    else if dex_program_id == RAYDIUM_CAMM_V2 {
-        raydium_clmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium CPMM (~16 accounts) ---
-    else if dex_program_id == RAYDIUM_CPMM {
-        raydium_cpmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Raydium CP-AMM new (~16 accounts, same handler as CPMM) ---
-    else if dex_program_id == RAYDIUM_CPAMM_NEW {
-        raydium_cpmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Orca Whirlpool (~11 accounts) ---
-    else if dex_program_id == ORCA_WHIRLPOOL {
-        orca_whirlpool_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Orca Whirlpool Legacy (~11 accounts, same handler) ---
-    else if dex_program_id == ORCA_WHIRLPOOL_LEGACY {
-        orca_whirlpool_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Pump.fun AMM (~15 accounts) ---
-    else if dex_program_id == PUMPFUN_AMM {
-        pumpfun_amm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- PancakeSwap AMM (~16 accounts) ---
-    else if dex_program_id == PANCAKESWAP {
-        pancakeswap_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Byreal CLMM (~12 accounts) ---
-    else if dex_program_id == BYREAL_CLMM {
-        byreal_clmm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Futarchy AMM (~14-15 accounts) ---
-    else if dex_program_id == FUTARCHY_AMM {
-        futarchy_amm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Fusion AMM (~14-15 accounts) ---
-    else if dex_program_id == FUSION_AMM {
-        fusion_amm_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // =========================================================================
-    // 10-account shared handler (FUN_ram_00005fe8, 2,904 bytes)
-    // These 5 DEXes share the same CPI layout: 10 accounts per hop
-    // =========================================================================
-    else if dex_program_id == ORCA_TOKEN_SWAP_V2 {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    else if dex_program_id == FLUXBEAM {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    else if dex_program_id == SABER_STABLE {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    else if dex_program_id == SAROS_SWAP {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    else if dex_program_id == STEPN_DOOAR {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    else if dex_program_id == PENGUIN_PHOENIX {
-        multi_dex_10_account_cpi(authority, remaining_accounts, account_offset, amount)?;
-    }
-    // --- Unknown DEX → error 6000 ---
-    else {
-        return Err(ArbBotError::InvalidInstructionData.into()); // 6000
+    let config = HopConfig {
+        hop_type: hop,
+        account_offset: cursor.position(),
+        num_accounts,
+        extra_accounts,
+        dex_program: entry.program_id,
+        amount_in: amount,
+        min_amount_out,
+    };
+
+    let destination = &hop_accounts[entry.roles.user_destination];
+    let balance_before = get_token_balance(destination)?;
+
+    (entry.handler)(authority, cursor, &config)?;
+    if cursor.position() != config.account_offset + num_accounts {
+        msg!("{} consumed {} accounts", entry.name, cursor.position() - config.account_offset);
+        return Err(ArbBotError::InvalidHopConfig.into());
     }
 
-    Ok(())
+    let amount_out = get_token_balance(destination)?
+        .checked_sub(balance_before)
+        .filter(|amount_out| *amount_out > 0)
+        .ok_or_else(|| {
+            msg!("{} produced no output", entry.name);
+            ArbBotError::CalculationError
+        })?;
+    if amount_out < min_amount_out {
+        msg!("{} out {} below min {}", entry.name, amount_out, min_amount_out);
+        return Err(ArbBotError::NotProfitable.into());
+    }
+    Ok(amount_out)
 }
 
 // =============================================================================
 // CPI Handlers (reconstructed from Ghidra decompilation)
 // =============================================================================
 
+/// Builds CPI account metas for a hop's accounts, skipping the program at [0].
+///
+/// Writability comes from the registry's writable mask; tail accounts past
+/// the fixed layout (bin arrays, tick arrays) are writable. Optional accounts
+/// passed as the DEX program id (Anchor's "None" placeholder) stay read-only.
+/// Signer flags are taken from the runtime, so only the authority signs.
+fn hop_account_metas(hop: &HopConfig, accounts: &[AccountInfo]) -> Result<Vec<AccountMeta>> {
+    let entry = hop.hop_type.dex()?;
+    let base = entry.accounts.base();
+
+    Ok(accounts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, account)| {
+            let writable = (i >= base || entry.writable_mask & (1 << i) != 0)
+                && *account.key != hop.dex_program;
+            if writable {
+                AccountMeta::new(*account.key, account.is_signer)
+            } else {
+                AccountMeta::new_readonly(*account.key, account.is_signer)
+            }
+        })
+        .collect())
+}
+
+/// The hop's user/authority slot must be the arb_swap signer.
+fn require_authority(account: &AccountInfo, authority: &Signer) -> Result<()> {
+    if account.key != authority.key {
+        msg!("hop authority mismatch: {}", account.key);
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+    Ok(())
+}
+
+/// Size the output of an exact-out swap from the pool vault balances
+/// (constant product), keeping `fee_headroom_bps` of the input budget back
+/// for fees the venue charges on top.
+fn exact_out_amount(
+    vault_in: &AccountInfo,
+    vault_out: &AccountInfo,
+    amount_in: u64,
+    fee_headroom_bps: u64,
+) -> Result<u64> {
+    let reserve_in = TokenAccountState::unpack(&vault_in.try_borrow_data()?)?.amount as u128;
+    let reserve_out = TokenAccountState::unpack(&vault_out.try_borrow_data()?)?.amount as u128;
+
+    let net_in = amount_in as u128 * (10_000 - fee_headroom_bps) as u128 / 10_000;
+    let amount_out = reserve_out
+        .checked_mul(net_in)
+        .and_then(|n| n.checked_div(reserve_in + net_in))
+        .and_then(|n| u64::try_from(n).ok())
+        .ok_or(ArbBotError::CalculationError)?;
+    if amount_out == 0 {
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+    Ok(amount_out)
+}
+
+/// Anchor discriminator for DLMM `swap2`: sha256("global:swap2")[..8].
+///
+/// The analysis JSON attributes 0xad837f01a485e633 / 0xeaebda01123d0666 to
+/// this handler, but those are Anchor's `sell` / `buy` discriminators
+/// (Pump.fun AMM), not DLMM's.
+const METEORA_DLMM_SWAP2: [u8; 8] = [65, 75, 63, 76, 235, 91, 91, 136];
+
 /// Meteora DLMM CPI — Swap2 instruction
 ///
 /// Ghidra source: FUN_ram_00005620 (1,792 bytes) — dedicated handler
-/// Account count: 21-23 (variable, extra bin_array accounts)
+/// Account count: 17 + bin arrays (variable, count from hop data)
 ///
-/// Account layout (from Ghidra struct access patterns):
+/// Account layout (matches the observed arb transaction):
 ///   [0]  program (Meteora DLMM)
 ///   [1]  lb_pair
-///   [2]  bin_array_bitmap_extension
+///   [2]  bin_array_bitmap_extension (program id if none)
 ///   [3]  reserve_x
 ///   [4]  reserve_y
-///   [5]  user_token_x
-///   [6]  user_token_y
+///   [5]  user_token_in
+///   [6]  user_token_out
 ///   [7]  token_x_mint
 ///   [8]  token_y_mint
 ///   [9]  oracle
-///   [10] host_fee_in
+///   [10] host_fee_in (program id if none)
 ///   [11] user/authority
 ///   [12] token_x_program
 ///   [13] token_y_program
-///   [14] event_authority
-///   [15..N] additional bin_array accounts (variable)
+///   [14] memo_program
+///   [15] event_authority
+///   [16] program (event CPI)
+///   [17..N] bin_array accounts (variable)
+///
+/// swap2 has no direction argument: DLMM infers it from which reserve the
+/// input account matches. The handler detects x→y / y→x from the mint of
+/// user_token_in and rejects a hop whose user accounts don't belong to the pair.
 fn meteora_dlmm_cpi<'info>(
-    _authority: &Signer<'info>,
-    remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    // Ghidra shows variable account count: reads a length field to determine
-    // how many bin_array accounts follow the fixed 15.
-    // Base accounts: 15, plus 0-8 bin arrays
-    let base_accounts = 15;
-    // In the binary, the number of extra bin arrays is encoded in hop data
-    let extra_bins = 6; // typical case from transaction analysis
-    let total_accounts = base_accounts + extra_bins;
-
-    let accounts: Vec<AccountMeta> = remaining_accounts[*account_offset..*account_offset + total_accounts]
-        .iter()
-        .enumerate()
-        .map(|(i, a)| {
-            if i == 0 { // program
-                AccountMeta::new_readonly(*a.key, false)
-            } else if i <= 6 { // mutable pool/user accounts
-                AccountMeta::new(*a.key, false)
-            } else {
-                AccountMeta::new_readonly(*a.key, false)
-            }
-        })
-        .collect();
+    let fixed = cursor.take(METEORA_DLMM_ACCOUNTS.base())?;
+    let bin_arrays = cursor.take_variable(hop.extra_accounts)?;
+    let accounts: Vec<AccountInfo<'info>> = fixed.iter().chain(bin_arrays).cloned().collect();
+    require_authority(&accounts[11], authority)?;
 
-    // Build CPI instruction with Swap2 discriminator
-    // (Anchor discriminator: sha256("global:swap2")[..8])
-    let _cpi_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id: METEORA_DLMM,
-        accounts,
-        data: Vec::new(), // actual swap data built from instruction params
+    let input_mint = TokenAccountState::unpack(&accounts[5].try_borrow_data()?)?.mint;
+    let output_mint = TokenAccountState::unpack(&accounts[6].try_borrow_data()?)?.mint;
+    let (mint_x, mint_y) = (*accounts[7].key, *accounts[8].key);
+    let x_to_y = match (input_mint, output_mint) {
+        (i, o) if i == mint_x && o == mint_y => true,
+        (i, o) if i == mint_y && o == mint_x => false,
+        _ => return Err(ArbBotError::InvalidHopConfig.into()),
     };
+    msg!("dlmm swap2 x_to_y: {}", x_to_y);
+
+    let mut data = Vec::with_capacity(28);
+    data.extend_from_slice(&METEORA_DLMM_SWAP2);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes());
+    // remaining_accounts_info: no transfer hook slices, bin arrays follow
+    data.extend_from_slice(&0u32.to_le_bytes());
 
-    // invoke(&cpi_ix, &accounts_infos)?;
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: hop_account_metas(hop, &accounts)?,
+        data,
+    };
+    invoke(&ix, &accounts)?;
 
-    *account_offset += total_accounts;
     Ok(())
 }
 
+/// Anchor discriminator sha256("global:swap")[..8], shared by the Meteora
+/// Dynamic AMM, Orca Whirlpool and Fusion AMM `swap` instructions.
+const ANCHOR_SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
 /// Meteora Dynamic AMM (Pools) CPI
-/// ~14-15 accounts per hop
+/// 16 accounts per hop
+///
+/// Account layout:
+///   [0]  program (Meteora Pools)
+///   [1]  pool
+///   [2]  user_source_token
+///   [3]  user_destination_token
+///   [4]  a_vault
+///   [5]  b_vault
+///   [6]  a_token_vault
+///   [7]  b_token_vault
+///   [8]  a_vault_lp_mint
+///   [9]  b_vault_lp_mint
+///   [10] a_vault_lp
+///   [11] b_vault_lp
+///   [12] protocol_token_fee (input side)
+///   [13] user (authority)
+///   [14] vault_program
+///   [15] token_program
+///
+/// The pool derives direction from user_source_token itself; the handler
+/// only checks the user accounts match the a/b vault tokens, and that the
+/// protocol fee account is for the input mint, so both directions route
+/// through the same layout.
 fn meteora_pools_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 15;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[13], authority)?;
+
+    let source_mint = TokenAccountState::unpack(&accounts[2].try_borrow_data()?)?.mint;
+    let destination_mint = TokenAccountState::unpack(&accounts[3].try_borrow_data()?)?.mint;
+    let mint_a = TokenAccountState::unpack(&accounts[6].try_borrow_data()?)?.mint;
+    let mint_b = TokenAccountState::unpack(&accounts[7].try_borrow_data()?)?.mint;
+    let fee_mint = TokenAccountState::unpack(&accounts[12].try_borrow_data()?)?.mint;
+
+    let a_to_b = match (source_mint, destination_mint) {
+        (i, o) if i == mint_a && o == mint_b => true,
+        (i, o) if i == mint_b && o == mint_a => false,
+        _ => return Err(ArbBotError::InvalidHopConfig.into()),
+    };
+    if fee_mint != source_mint {
+        msg!("protocol fee account mint mismatch: {}", fee_mint);
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+    msg!("pools swap a_to_b: {}", a_to_b);
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&ANCHOR_SWAP);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes()); // in_amount
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // minimum_out_amount
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: hop_account_metas(hop, accounts)?,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
-/// Raydium AMM V4 CPI — Swap instruction
-/// ~17 accounts per hop (includes OpenBook/Serum market accounts)
+/// Raydium AMM V4 instruction tag for SwapBaseIn.
+const RAYDIUM_AMM_V4_SWAP_BASE_IN: u8 = 9;
+
+/// Raydium AMM V4 CPI — SwapBaseIn instruction
+/// 19 accounts per hop (includes OpenBook/Serum market accounts)
 ///
-/// Account layout (from Ghidra analysis):
+/// Account layout (from Ghidra analysis, plus signer and token program):
 ///   [0]  program (Raydium AMM V4)
 ///   [1]  amm_id
 ///   [2]  amm_authority
@@ -413,161 +1272,524 @@ fn meteora_pools_cpi<'info>(
 ///   [14] serum_vault_signer
 ///   [15] user_source
 ///   [16] user_destination
+///   [17] user_owner (authority)
+///   [18] token_program (SPL Token)
+///
+/// SwapBaseIn expects the token program first, so [18] is moved to the front
+/// of the CPI metas; the rest keep hop-layout order.
 fn raydium_amm_v4_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 17;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[17], authority)?;
+    if *accounts[18].key != spl_token::id() {
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    let mut data = Vec::with_capacity(17);
+    data.push(RAYDIUM_AMM_V4_SWAP_BASE_IN);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes());
+
+    let mut metas = hop_account_metas(hop, accounts)?;
+    metas.rotate_right(1);
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
-/// Raydium CLMM CPI — shared handler for CLMM, CLMM-openbook, CAMM V2
-/// ~13 accounts per hop
+/// Anchor discriminator for CLMM `swap_v2`: sha256("global:swap_v2")[..8].
+/// (0xc88775e1919ec6f8 in FUN_ram_00005d20 / FUN_ram_00007858 is the v1 `swap`.)
+const RAYDIUM_CLMM_SWAP_V2: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// Raydium CLMM sqrt price bounds (Q64.64), from the CLMM tick math.
+const CLMM_MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+const CLMM_MAX_SQRT_PRICE_X64: u128 = 79_226_673_521_066_979_257_578_248_091;
+
+/// Raydium CLMM CPI — swap_v2, shared handler for CLMM, CLMM-openbook, CAMM V2
+/// and the Raydium CLMM forks (PancakeSwap, Byreal CLMM)
+/// 14 accounts + optional tick-array tail (count from hop data)
+///
+/// Ghidra source: FUN_ram_00007858 (16-account CLMM CPI, i.e. 14 + 2 tail)
 ///
 /// Account layout:
 ///   [0]  program
-///   [1]  amm_config
-///   [2]  pool_state
-///   [3]  input_token_account
-///   [4]  output_token_account
-///   [5]  input_vault
-///   [6]  output_vault
-///   [7]  observation_state
-///   [8]  token_program
-///   [9]  token_program_2022 (if needed)
-///   [10] memo_program
-///   [11] input_vault_mint
-///   [12] output_vault_mint
+///   [1]  payer (authority)
+///   [2]  amm_config
+///   [3]  pool_state
+///   [4]  input_token_account
+///   [5]  output_token_account
+///   [6]  input_vault
+///   [7]  output_vault
+///   [8]  observation_state
+///   [9]  token_program
+///   [10] token_program_2022
+///   [11] memo_program
+///   [12] input_vault_mint
+///   [13] output_vault_mint
+///   [14..N] tick_array_bitmap_extension / tick arrays (variable)
+///
+/// Direction (FUN_ram_00007858): CLMM pools order their mints so that
+/// token_mint_0 < token_mint_1, so zero_for_one is input_vault_mint <
+/// output_vault_mint. The sqrt price limit is set to the far bound in that
+/// direction, leaving min_amount_out as the only price guard.
 fn raydium_clmm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 13;
+    let fixed = cursor.take(RAYDIUM_CLMM_ACCOUNTS.base())?;
+    let tick_arrays = cursor.take_variable(hop.extra_accounts)?;
+    let accounts: Vec<AccountInfo<'info>> = fixed.iter().chain(tick_arrays).cloned().collect();
+    require_authority(&accounts[1], authority)?;
+
+    let (input_mint, output_mint) = (*accounts[12].key, *accounts[13].key);
+    if TokenAccountState::unpack(&accounts[4].try_borrow_data()?)?.mint != input_mint
+        || TokenAccountState::unpack(&accounts[5].try_borrow_data()?)?.mint != output_mint
+    {
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    let zero_for_one = input_mint < output_mint;
+    let sqrt_price_limit_x64 = if zero_for_one {
+        CLMM_MIN_SQRT_PRICE_X64 + 1
+    } else {
+        CLMM_MAX_SQRT_PRICE_X64 - 1
+    };
+
+    let mut data = Vec::with_capacity(41);
+    data.extend_from_slice(&RAYDIUM_CLMM_SWAP_V2);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // other_amount_threshold
+    data.extend_from_slice(&sqrt_price_limit_x64.to_le_bytes());
+    data.push(1); // is_base_input
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: hop_account_metas(hop, &accounts)?,
+        data,
+    };
+    invoke(&ix, &accounts)?;
+
     Ok(())
 }
 
+/// Anchor discriminators for CPMM `swap_base_input` / `swap_base_output`.
+const RAYDIUM_CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+const RAYDIUM_CPMM_SWAP_BASE_OUTPUT: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+/// Fee headroom (bps) kept back from max_amount_in when sizing an exact-out
+/// CPMM swap. The highest CPMM amm_config trade fee tier is 4%.
+const RAYDIUM_CPMM_FEE_HEADROOM_BPS: u64 = 400;
+
 /// Raydium CPMM / CP-AMM CPI — shared handler
-/// ~16 accounts per hop
+/// 14 accounts per hop
+///
+/// Account layout:
+///   [0]  program (CPMM or CP-AMM new)
+///   [1]  payer (authority)
+///   [2]  authority (pool vault PDA)
+///   [3]  amm_config
+///   [4]  pool_state
+///   [5]  input_token_account
+///   [6]  output_token_account
+///   [7]  input_vault
+///   [8]  output_vault
+///   [9]  input_token_program
+///   [10] output_token_program
+///   [11] input_token_mint
+///   [12] output_token_mint
+///   [13] observation_state
+///
+/// Hop types 0x06/0x07 are exact-in (`swap_base_input`); 0x18/0x19 are
+/// exact-out (`swap_base_output`) with amount_in as max_amount_in. The exact
+/// amount_out is the hop's min_amount_out when set, otherwise it is sized
+/// from the vault balances.
 fn raydium_cpmm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 16;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[1], authority)?;
+
+    if *accounts[9].key != get_token_program_for_mint(&accounts[11])
+        || *accounts[10].key != get_token_program_for_mint(&accounts[12])
+    {
+        msg!("CPMM token program mismatch");
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    let mut data = Vec::with_capacity(24);
+    if hop.hop_type.is_exact_out() {
+        let amount_out = match hop.min_amount_out {
+            0 => exact_out_amount(
+                &accounts[7],
+                &accounts[8],
+                hop.amount_in,
+                RAYDIUM_CPMM_FEE_HEADROOM_BPS,
+            )?,
+            amount_out => amount_out,
+        };
+        data.extend_from_slice(&RAYDIUM_CPMM_SWAP_BASE_OUTPUT);
+        data.extend_from_slice(&hop.amount_in.to_le_bytes()); // max_amount_in
+        data.extend_from_slice(&amount_out.to_le_bytes());
+    } else {
+        data.extend_from_slice(&RAYDIUM_CPMM_SWAP_BASE_INPUT);
+        data.extend_from_slice(&hop.amount_in.to_le_bytes());
+        data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // minimum_amount_out
+    }
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: hop_account_metas(hop, accounts)?,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
-/// Orca Whirlpool CPI — shared handler for current + legacy
-/// ~11 accounts per hop
+/// Whirlpool sqrt price bounds (Q64.64), from the Whirlpool tick math.
+const WHIRLPOOL_MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+const WHIRLPOOL_MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+/// Orca Whirlpool CPI
+/// 12 accounts per hop
 ///
 /// Account layout:
 ///   [0]  program (Whirlpool)
 ///   [1]  token_program
-///   [2]  token_authority
+///   [2]  token_authority (authority)
 ///   [3]  whirlpool
-///   [4]  token_owner_account_a
+///   [4]  user_source
 ///   [5]  token_vault_a
-///   [6]  token_owner_account_b
+///   [6]  user_destination
 ///   [7]  token_vault_b
 ///   [8]  tick_array_0
 ///   [9]  tick_array_1
 ///   [10] tick_array_2
 ///   [11] oracle
+///
+/// Whirlpool expects token_owner_account_a / token_owner_account_b, so the
+/// user accounts are listed in in/out order and swapped into a/b order once
+/// a_to_b is known (source mint == vault_a mint).
 fn orca_whirlpool_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 11;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[2], authority)?;
+
+    let source_mint = TokenAccountState::unpack(&accounts[4].try_borrow_data()?)?.mint;
+    let destination_mint = TokenAccountState::unpack(&accounts[6].try_borrow_data()?)?.mint;
+    let vault_a_mint = TokenAccountState::unpack(&accounts[5].try_borrow_data()?)?.mint;
+    let vault_b_mint = TokenAccountState::unpack(&accounts[7].try_borrow_data()?)?.mint;
+
+    let a_to_b = if source_mint == vault_a_mint && destination_mint == vault_b_mint {
+        true
+    } else if source_mint == vault_b_mint && destination_mint == vault_a_mint {
+        false
+    } else {
+        return Err(ArbBotError::InvalidHopConfig.into());
+    };
+    let sqrt_price_limit = if a_to_b {
+        WHIRLPOOL_MIN_SQRT_PRICE_X64
+    } else {
+        WHIRLPOOL_MAX_SQRT_PRICE_X64
+    };
+
+    let mut metas = hop_account_metas(hop, accounts)?;
+    if !a_to_b {
+        // metas skip the program account, so [4]/[6] sit at 3/5
+        metas.swap(3, 5);
+    }
+
+    let mut data = Vec::with_capacity(42);
+    data.extend_from_slice(&ANCHOR_SWAP);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // other_amount_threshold
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
-/// Pump.fun AMM CPI — Sell instruction
-/// ~15 accounts per hop
+/// Anchor discriminators for Pump.fun AMM `sell` / `buy`
+/// (0xad837f01a485e633 / 0xeaebda01123d0666 in the analysis JSON).
+const PUMPFUN_SELL: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const PUMPFUN_BUY: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+
+/// Fee headroom (bps) left out of the quote budget when sizing a buy.
+/// Pump.fun AMM charges LP + protocol + creator fees on top of the quote
+/// needed for `base_amount_out`; these are read by the fee program from
+/// fee_config and are well below 1%.
+const PUMPFUN_BUY_FEE_HEADROOM_BPS: u64 = 100;
+
+/// Pump.fun AMM CPI — Sell (base → quote) / Buy (quote → base)
+/// 22 accounts (sell) / 24 accounts (buy) per hop
 ///
-/// Also internally calls Pump.fun fee program (pfeeUxB6...) for GetFees CPI.
+/// The AMM internally calls the Pump.fun fee program (pfeeUxB6...) to
+/// read the fee tiers from fee_config, so both have to be passed through.
 ///
-/// Account layout:
+/// Account layout (matches the observed arb transaction):
 ///   [0]  program (pAMMBay6...)
-///   [1]  global_config
-///   [2]  pool
-///   [3]  user_base_token_account
-///   [4]  user_quote_token_account
-///   [5]  pool_base_token_vault
-///   [6]  pool_quote_token_vault
-///   [7]  base_token_mint
-///   [8]  quote_token_mint
-///   [9]  fee_recipient
-///   [10] fee_program (pfeeUxB6...)
-///   [11] token_program
-///   [12] token_2022_program (if needed)
-///   [13] system_program
-///   [14] event_authority
+///   [1]  pool
+///   [2]  user (authority)
+///   [3]  global_config
+///   [4]  base_mint
+///   [5]  quote_mint
+///   [6]  user_base_token_account
+///   [7]  user_quote_token_account
+///   [8]  pool_base_token_account
+///   [9]  pool_quote_token_account
+///   [10] protocol_fee_recipient
+///   [11] protocol_fee_recipient_token_account
+///   [12] base_token_program
+///   [13] quote_token_program
+///   [14] system_program
+///   [15] associated_token_program
+///   [16] event_authority
+///   [17] program (pAMMBay6...)
+///   [18] coin_creator_vault_ata
+///   [19] coin_creator_vault_authority
+///   buy only:
+///   [20] global_volume_accumulator
+///   [21] user_volume_accumulator
+///   [N-2] fee_config
+///   [N-1] fee_program (pfeeUxB6...)
+///
+/// Sell is exact-in (base_amount_in, min_quote_amount_out). Buy is exact-out
+/// (base_amount_out, max_quote_amount_in), so base_amount_out is sized from
+/// the pool reserves with the quote budget minus the fee headroom.
 fn pumpfun_amm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 15;
-    Ok(())
-}
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[2], authority)?;
 
-/// PancakeSwap AMM CPI — ~16 accounts per hop
-fn pancakeswap_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
-) -> Result<()> {
-    *account_offset += 16;
-    Ok(())
-}
+    if *accounts[hop.num_accounts - 1].key != PUMPFUN_FEE {
+        msg!("Pump.fun fee program mismatch: {}", accounts[hop.num_accounts - 1].key);
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+    if *accounts[12].key != get_token_program_for_mint(&accounts[4])
+        || *accounts[13].key != get_token_program_for_mint(&accounts[5])
+    {
+        msg!("Pump.fun token program mismatch");
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    let mut data = Vec::with_capacity(24);
+    match hop.hop_type.direction() {
+        SwapDirection::BaseToQuote => {
+            data.extend_from_slice(&PUMPFUN_SELL);
+            data.extend_from_slice(&hop.amount_in.to_le_bytes()); // base_amount_in
+            data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // min_quote_amount_out
+        }
+        SwapDirection::QuoteToBase => {
+            let base_amount_out = exact_out_amount(
+                &accounts[9],
+                &accounts[8],
+                hop.amount_in,
+                PUMPFUN_BUY_FEE_HEADROOM_BPS,
+            )?;
+            if base_amount_out < hop.min_amount_out {
+                return Err(ArbBotError::InvalidHopConfig.into());
+            }
+
+            data.extend_from_slice(&PUMPFUN_BUY);
+            data.extend_from_slice(&base_amount_out.to_le_bytes());
+            data.extend_from_slice(&hop.amount_in.to_le_bytes()); // max_quote_amount_in
+        }
+        SwapDirection::FromAccounts => return Err(ArbBotError::InvalidHopConfig.into()),
+    }
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: hop_account_metas(hop, accounts)?,
+        data,
+    };
+    invoke(&ix, accounts)?;
 
-/// Byreal CLMM CPI — ~12 accounts per hop
-fn byreal_clmm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
-) -> Result<()> {
-    *account_offset += 12;
     Ok(())
 }
 
-/// Futarchy AMM CPI — ~14-15 accounts per hop
+/// Anchor discriminator for Futarchy `spot_swap` (0xfba64eede70c61a7).
+const FUTARCHY_SPOT_SWAP: [u8; 8] = [167, 97, 12, 231, 237, 78, 166, 251];
+
+/// Futarchy `SwapType` argument.
+const FUTARCHY_SWAP_BUY: u8 = 0x00;
+const FUTARCHY_SWAP_SELL: u8 = 0x01;
+
+/// Futarchy AMM CPI — spot_swap (directional)
+///
+/// Ghidra source: FUN_ram_00007c80 (2,792 bytes) — 9 CPI accounts,
+/// direction byte 0x00/0x01
+///
+/// Account layout:
+///   [0]  program (Futarchy)
+///   [1]  dao
+///   [2]  user_source
+///   [3]  user_destination
+///   [4]  amm_base_vault
+///   [5]  amm_quote_vault
+///   [6]  user (authority)
+///   [7]  token_program
+///   [8]  event_authority
+///   [9]  program (Futarchy)
+///
+/// spot_swap takes user_base_account / user_quote_account, so the user
+/// accounts are swapped into base/quote order for a buy (quote → base).
 fn futarchy_amm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 15;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[6], authority)?;
+
+    let source_mint = TokenAccountState::unpack(&accounts[2].try_borrow_data()?)?.mint;
+    let destination_mint = TokenAccountState::unpack(&accounts[3].try_borrow_data()?)?.mint;
+    let base_mint = TokenAccountState::unpack(&accounts[4].try_borrow_data()?)?.mint;
+    let quote_mint = TokenAccountState::unpack(&accounts[5].try_borrow_data()?)?.mint;
+
+    let swap_type = match (source_mint, destination_mint) {
+        (i, o) if i == base_mint && o == quote_mint => FUTARCHY_SWAP_SELL,
+        (i, o) if i == quote_mint && o == base_mint => FUTARCHY_SWAP_BUY,
+        _ => return Err(ArbBotError::InvalidHopConfig.into()),
+    };
+
+    let mut metas = hop_account_metas(hop, accounts)?;
+    if swap_type == FUTARCHY_SWAP_BUY {
+        // metas skip the program account, so [2]/[3] sit at 1/2
+        metas.swap(1, 2);
+    }
+
+    let mut data = Vec::with_capacity(25);
+    data.extend_from_slice(&FUTARCHY_SPOT_SWAP);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes()); // input_amount
+    data.push(swap_type);
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // min_output_amount
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
-/// Fusion AMM CPI — ~14-15 accounts per hop
+/// Fusion AMM CPI — swap (Whirlpool-derived CLMM)
+///
+/// Ghidra source: FUN_ram_00006b48 (696 bytes) — 14 CPI accounts. The 0x01
+/// the analysis reads as an instruction byte is amount_specified_is_input.
+///
+/// Account layout:
+///   [0]  program (Fusion)
+///   [1]  token_program_a
+///   [2]  token_program_b
+///   [3]  memo_program
+///   [4]  token_authority (authority)
+///   [5]  fusion_pool
+///   [6]  token_mint_a
+///   [7]  token_mint_b
+///   [8]  user_source
+///   [9]  token_vault_a
+///   [10] user_destination
+///   [11] token_vault_b
+///   [12] tick_array_0
+///   [13] tick_array_1
+///   [14] tick_array_2
+///
+/// Same direction handling as Orca Whirlpool: user accounts in in/out order,
+/// swapped into a/b order when b → a. Fusion keeps Whirlpool's tick math, so
+/// the sqrt price limits are Orca's bounds (WHIRLPOOL_*_SQRT_PRICE_X64), not
+/// Raydium CLMM's.
 fn fusion_amm_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    *account_offset += 15;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[4], authority)?;
+
+    if *accounts[1].key != get_token_program_for_mint(&accounts[6])
+        || *accounts[2].key != get_token_program_for_mint(&accounts[7])
+    {
+        msg!("Fusion token program mismatch");
+        return Err(ArbBotError::InvalidHopConfig.into());
+    }
+
+    let source_mint = TokenAccountState::unpack(&accounts[8].try_borrow_data()?)?.mint;
+    let destination_mint = TokenAccountState::unpack(&accounts[10].try_borrow_data()?)?.mint;
+    let (mint_a, mint_b) = (*accounts[6].key, *accounts[7].key);
+
+    let a_to_b = match (source_mint, destination_mint) {
+        (i, o) if i == mint_a && o == mint_b => true,
+        (i, o) if i == mint_b && o == mint_a => false,
+        _ => return Err(ArbBotError::InvalidHopConfig.into()),
+    };
+    let sqrt_price_limit = if a_to_b {
+        WHIRLPOOL_MIN_SQRT_PRICE_X64
+    } else {
+        WHIRLPOOL_MAX_SQRT_PRICE_X64
+    };
+
+    let mut metas = hop_account_metas(hop, accounts)?;
+    if !a_to_b {
+        // metas skip the program account, so [8]/[10] sit at 7/9
+        metas.swap(7, 9);
+    }
+
+    let mut data = Vec::with_capacity(43);
+    data.extend_from_slice(&ANCHOR_SWAP);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes()); // other_amount_threshold
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+    data.push(0); // remaining_accounts_info: None
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
+/// spl-token-swap `Swap` instruction tag (stable-swap uses the same tag).
+const TOKEN_SWAP_IX_SWAP: u8 = 0x01;
+
 /// Multi-DEX 10-account shared CPI handler
 ///
 /// Ghidra source: FUN_ram_00005fe8 (2,904 bytes)
 ///
-/// Serves 6 DEXes that share an identical 10-account layout:
+/// Serves 7 DEXes that share an identical 10-account layout:
+///   - Orca Token Swap V1 (DjVE6, the pre-Whirlpool Orca pools)
 ///   - Orca Token Swap V2
 ///   - Fluxbeam DEX
 ///   - Saber Stable Swap
@@ -575,26 +1797,48 @@ fn fusion_amm_cpi<'info>(
 ///   - StepN DEX (Dooar)
 ///   - Penguin Swap / Phoenix
 ///
-/// Account layout (standard token-swap interface):
+/// Account layout (standard token-swap interface, plus token program):
 ///   [0]  program
 ///   [1]  swap_pool
 ///   [2]  swap_authority (PDA)
-///   [3]  user_transfer_authority
+///   [3]  user_transfer_authority (authority)
 ///   [4]  user_source_token
 ///   [5]  pool_source_token
 ///   [6]  pool_dest_token
 ///   [7]  user_dest_token
-///   [8]  pool_mint
-///   [9]  pool_fee_account
+///   [8]  pool_mint           (Saber: admin_fee_destination)
+///   [9]  pool_fee_account    (Saber: clock sysvar)
+///   [10] token_program
+///
+/// Saber's stable-swap has no pool mint / fee account on swaps and expects
+/// `..., admin_fee_destination, token_program, clock`, so its metas are
+/// reordered before the CPI.
 fn multi_dex_10_account_cpi<'info>(
-    _authority: &Signer<'info>,
-    _remaining_accounts: &[AccountInfo<'info>],
-    account_offset: &mut usize,
-    _amount: u64,
+    authority: &Signer<'info>,
+    cursor: &mut AccountCursor<'_, 'info>,
+    hop: &HopConfig,
 ) -> Result<()> {
-    // All 6 DEXes use the standard spl-token-swap interface:
-    // Instruction 1 (Swap) with amount_in, minimum_amount_out
-    *account_offset += 10;
+    let accounts = cursor.take(hop.num_accounts)?;
+    require_authority(&accounts[3], authority)?;
+
+    let mut metas = hop_account_metas(hop, accounts)?;
+    if hop.dex_program == SABER_STABLE {
+        // metas skip the program account: clock at 8, token_program at 9
+        metas.swap(8, 9);
+    }
+
+    let mut data = Vec::with_capacity(17);
+    data.push(TOKEN_SWAP_IX_SWAP);
+    data.extend_from_slice(&hop.amount_in.to_le_bytes());
+    data.extend_from_slice(&hop.min_amount_out.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: hop.dex_program,
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, accounts)?;
+
     Ok(())
 }
 
@@ -669,6 +1913,41 @@ pub fn process_create_token_account(ctx: Context<CreateTokenAccount>) -> Result<
     Ok(())
 }
 
+// =============================================================================
+// Instructions 0x01 / 0x03 / 0x04: Config admin (not in the original binary)
+// =============================================================================
+
+/// Creates the Config PDA. The upgrade authority (checked on the accounts)
+/// becomes its admin.
+pub fn process_initialize_config(
+    ctx: Context<InitializeConfig>,
+    params: ConfigParams,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.bump = ctx.bumps.config;
+    config.apply(params);
+
+    msg!("config initialized, admin: {}", config.admin);
+    Ok(())
+}
+
+/// Replaces the Config settings (admin only).
+pub fn process_update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    ctx.accounts.config.apply(params);
+
+    msg!("config updated");
+    Ok(())
+}
+
+/// Hands the Config over to `new_admin` (admin only).
+pub fn process_set_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.admin = new_admin;
+
+    msg!("config admin: {}", new_admin);
+    Ok(())
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
@@ -681,6 +1960,35 @@ fn derive_account_seed(mint: &Pubkey) -> String {
     hex::encode(&hash.to_bytes()[..16]) // first 16 bytes = 32 hex chars
 }
 
+/// Only the upgrade authority (from ProgramData) and the Config's operators
+/// may use the program, so it can't be borrowed as a routing venue.
+fn check_operator(authority: &Signer, program_data: &ProgramData, config: &Config) -> Result<()> {
+    if program_data.upgrade_authority_address != Some(*authority.key)
+        && !config.operators.contains(authority.key)
+    {
+        msg!("unauthorized operator: {}", authority.key);
+        return Err(ArbBotError::UnauthorizedOperator.into());
+    }
+    Ok(())
+}
+
+/// The source/destination accounts must be live token accounts of
+/// `authority`: owned by SPL Token or Token-2022, owner field == authority,
+/// not frozen. Anything else is InvalidAccountState.
+fn check_user_token_account(account: &AccountInfo, authority: &Signer) -> Result<()> {
+    if *account.owner != spl_token::id() && !is_token_2022(account.owner) {
+        msg!("not a token account: {}", account.key);
+        return Err(ArbBotError::InvalidAccountState.into());
+    }
+    let state = TokenAccountState::unpack(&account.try_borrow_data()?)
+        .map_err(|_| ArbBotError::InvalidAccountState)?;
+    if state.owner != *authority.key || state.is_frozen {
+        msg!("token account not usable by authority: {}", account.key);
+        return Err(ArbBotError::InvalidAccountState.into());
+    }
+    Ok(())
+}
+
 /// Check if a token account's mint is Wrapped SOL.
 /// Ghidra: FUN_ram_00002368 does memcmp against WSOL mint (So111...)
 fn is_wsol_mint(account: &AccountInfo) -> Result<bool> {
@@ -693,6 +2001,54 @@ fn is_wsol_mint(account: &AccountInfo) -> Result<bool> {
     Ok(mint == WSOL_MINT)
 }
 
+/// Tip and minimum-profit math for a profitable cycle, in profit-token units.
+///
+/// tip = profit * tip_bps / 10000, raised to `min_tip` and capped at `profit`
+/// (so the floor can't turn a win into a loss), or 0 with FLAG_SKIP_TIP.
+/// The profit left after the tip must reach `min_profit` (absolute, or bps
+/// of `amount` with FLAG_MIN_PROFIT_BPS), else NotProfitable. Returns the tip.
+fn settle_tip(
+    profit: u64,
+    amount: u64,
+    flags: u16,
+    tip_bps: u16,
+    min_tip: u64,
+    min_profit: u64,
+) -> Result<u64> {
+    let tip = if flags & FLAG_SKIP_TIP != 0 {
+        0
+    } else {
+        let tip = (profit as u128 * tip_bps as u128 / 10_000) as u64;
+        tip.max(min_tip).min(profit)
+    };
+
+    let min_profit = if flags & FLAG_MIN_PROFIT_BPS != 0 {
+        (amount as u128 * min_profit as u128 / 10_000).min(u64::MAX as u128) as u64
+    } else {
+        min_profit
+    };
+    let net_profit = profit - tip;
+    if net_profit < min_profit {
+        msg!("net profit {} below min {}", net_profit, min_profit);
+        return Err(ArbBotError::NotProfitable.into());
+    }
+
+    Ok(tip)
+}
+
+/// Converts profit-token units to lamports at a TIP_PRICE_SCALE price.
+fn tokens_to_lamports(tokens: u64, lamports_per_token_e9: u64) -> Result<u64> {
+    u64::try_from(tokens as u128 * lamports_per_token_e9 as u128 / TIP_PRICE_SCALE)
+        .map_err(|_| ArbBotError::CalculationError.into())
+}
+
+/// Converts lamports to profit-token units at a TIP_PRICE_SCALE price,
+/// rounding up so the converted amount is worth at least `lamports`.
+fn lamports_to_tokens(lamports: u64, lamports_per_token_e9: u64) -> Result<u64> {
+    u64::try_from((lamports as u128 * TIP_PRICE_SCALE).div_ceil(lamports_per_token_e9 as u128))
+        .map_err(|_| ArbBotError::CalculationError.into())
+}
+
 /// Get the token balance from a token account.
 /// SPL Token account layout: amount is at bytes 64..72 (little-endian u64)
 fn get_token_balance(account: &AccountInfo) -> Result<u64> {
@@ -703,3 +2059,338 @@ fn get_token_balance(account: &AccountInfo) -> Result<u64> {
     let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
     Ok(amount)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IX_ARB_SWAP, IX_CREATE_TOKEN_ACCOUNT};
+
+    const AMOUNT: u64 = 1_000_000_000;
+
+    /// arb_swap data: header, then `tail` (hop types and optional sections).
+    fn arb_swap_data(num_hops: u8, flags: u16, tail: &[u8]) -> Vec<u8> {
+        let mut data = vec![IX_ARB_SWAP, num_hops];
+        data.extend_from_slice(&AMOUNT.to_le_bytes());
+        data.extend_from_slice(&flags.to_le_bytes());
+        data.extend_from_slice(tail);
+        data
+    }
+
+    fn invalid_data() -> Error {
+        ArbBotError::InvalidInstructionData.into()
+    }
+
+    // --- decode_arb_swap_data ---
+
+    #[test]
+    fn decode_exact_header() {
+        // 0x02 then 0x06: low nibble first, no padding
+        let args = decode_arb_swap_data(&arb_swap_data(2, 25, &[0x62])).unwrap();
+        assert_eq!(
+            args,
+            ArbSwapArgs {
+                num_hops: 2,
+                amount: AMOUNT,
+                flags: 25,
+                hop_types: vec![0x62],
+                hop_extra_accounts: vec![0, 0],
+                hop_min_out: vec![],
+                min_profit: 0,
+                tip_bps: 2_500,
+                tip_mode: TipMode::Lamports,
+            }
+        );
+    }
+
+    #[test]
+    fn decode_odd_nibble_padding() {
+        let args = decode_arb_swap_data(&arb_swap_data(1, 0, &[0xF2])).unwrap();
+        assert_eq!(args.hop_types, vec![0xF2]);
+        assert_eq!(args.hop_extra_accounts, vec![0]);
+    }
+
+    #[test]
+    fn decode_escape_nibble() {
+        // 0xF, 0x3 → 0x12 (Fusion AMM), then 0x02 and a pad nibble
+        let args = decode_arb_swap_data(&arb_swap_data(2, 0, &[0x3F, 0xF2])).unwrap();
+        assert_eq!(args.hop_types, vec![0x3F, 0xF2]);
+        assert_eq!(
+            expand_hop_types(&args.hop_types).unwrap(),
+            vec![HopType::FusionAmm, HopType::RaydiumAmmV4]
+        );
+    }
+
+    #[test]
+    fn decode_rejects_truncated_buffers() {
+        let data = arb_swap_data(1, 0, &[0xF2]);
+        for len in 0..data.len() {
+            assert_eq!(decode_arb_swap_data(&data[..len]).unwrap_err(), invalid_data());
+        }
+        // escape nibble with nothing after it
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(2, 0, &[0xF2])).unwrap_err(),
+            invalid_data()
+        );
+        // flagged min_profit missing
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, FLAG_MIN_PROFIT, &[0xF2, 1, 2, 3])).unwrap_err(),
+            invalid_data()
+        );
+    }
+
+    #[test]
+    fn decode_rejects_trailing_bytes() {
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, 0, &[0xF2, 0x00])).unwrap_err(),
+            invalid_data()
+        );
+    }
+
+    #[test]
+    fn decode_rejects_bad_pad_nibble() {
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, 0, &[0x62])).unwrap_err(),
+            invalid_data()
+        );
+    }
+
+    #[test]
+    fn decode_rejects_bad_header() {
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(0, 0, &[])).unwrap_err(),
+            invalid_data()
+        );
+        let mut data = arb_swap_data(1, 0, &[0xF2]);
+        data[0] = IX_CREATE_TOKEN_ACCOUNT;
+        assert_eq!(decode_arb_swap_data(&data).unwrap_err(), invalid_data());
+    }
+
+    #[test]
+    fn decode_v0_tip_percent() {
+        let args = decode_arb_swap_data(&arb_swap_data(1, 100, &[0xF2])).unwrap();
+        assert_eq!(args.tip_bps, MAX_TIP_BPS);
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, 101, &[0xF2])).unwrap_err(),
+            invalid_data()
+        );
+    }
+
+    #[test]
+    fn decode_optional_sections() {
+        let flags = (FLAGS_V1 << FLAGS_VERSION_SHIFT)
+            | FLAG_V1_TIP_PRICED
+            | FLAG_V1_EXTRA_ACCOUNTS
+            | FLAG_HOP_MIN_OUT
+            | FLAG_MIN_PROFIT;
+        let mut tail = vec![0xF0, 3];
+        tail.extend_from_slice(&7u64.to_le_bytes());
+        tail.extend_from_slice(&9u64.to_le_bytes());
+        tail.extend_from_slice(&250u16.to_le_bytes());
+        tail.extend_from_slice(&5u64.to_le_bytes());
+
+        let args = decode_arb_swap_data(&arb_swap_data(1, flags, &tail)).unwrap();
+        assert_eq!(args.hop_extra_accounts, vec![3]);
+        assert_eq!(args.hop_min_out, vec![7]);
+        assert_eq!(args.min_profit, 9);
+        assert_eq!(args.tip_bps, 250);
+        assert_eq!(args.tip_mode, TipMode::Priced { lamports_per_token_e9: 5 });
+    }
+
+    #[test]
+    fn decode_rejects_dlmm_without_bin_arrays() {
+        // v0: no extra-account section, so DLMM (0x00) would get none
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, 0, &[0xF0])).unwrap_err(),
+            invalid_data()
+        );
+        // v1 with the section, but a zero count
+        let flags = (FLAGS_V1 << FLAGS_VERSION_SHIFT) | FLAG_V1_EXTRA_ACCOUNTS;
+        let mut tail = vec![0xF0, 0];
+        tail.extend_from_slice(&0u16.to_le_bytes());
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, flags, &tail)).unwrap_err(),
+            invalid_data()
+        );
+        tail[1] = 2;
+        let args = decode_arb_swap_data(&arb_swap_data(1, flags, &tail)).unwrap();
+        assert_eq!(args.hop_extra_accounts, vec![2]);
+    }
+
+    #[test]
+    fn decode_rejects_deployed_header_only_format() {
+        // The recorded on-chain arb_swap: num_hops=2, v0 flags=75, no hop types
+        let data = [0x00, 0x02, 0x4e, 0x94, 0xaf, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x4b, 0x00];
+        assert_eq!(decode_arb_swap_data(&data).unwrap_err(), invalid_data());
+    }
+
+    #[test]
+    fn decode_rejects_unknown_v1_low_bits() {
+        let v1 = FLAGS_V1 << FLAGS_VERSION_SHIFT;
+        let mut tail = vec![0xF2];
+        tail.extend_from_slice(&0u16.to_le_bytes());
+        assert!(decode_arb_swap_data(&arb_swap_data(1, v1, &tail)).is_ok());
+        for bit in [0x08, 0x10, 0x80] {
+            assert_eq!(
+                decode_arb_swap_data(&arb_swap_data(1, v1 | bit, &tail)).unwrap_err(),
+                invalid_data()
+            );
+        }
+    }
+
+    #[test]
+    fn decode_rejects_unknown_version() {
+        assert_eq!(
+            decode_arb_swap_data(&arb_swap_data(1, 2 << FLAGS_VERSION_SHIFT, &[0xF2])).unwrap_err(),
+            invalid_data()
+        );
+    }
+
+    // --- packed_hop_types_len ---
+
+    #[test]
+    fn packed_len_counts_pad_and_escape() {
+        assert_eq!(packed_hop_types_len(&[0x62], 2).unwrap(), 1);
+        assert_eq!(packed_hop_types_len(&[0xF2], 1).unwrap(), 1);
+        assert_eq!(packed_hop_types_len(&[0x3F], 1).unwrap(), 1);
+        assert_eq!(packed_hop_types_len(&[0x3F, 0xF2, 0xAA], 2).unwrap(), 2);
+    }
+
+    #[test]
+    fn packed_len_rejects_short_input_and_high_types() {
+        assert_eq!(packed_hop_types_len(&[0x62], 3).unwrap_err(), invalid_data());
+        assert_eq!(packed_hop_types_len(&[0x12], 1).unwrap_err(), invalid_data());
+        // 0xF, 0xF → 0x1e, above 0x1a
+        assert_eq!(packed_hop_types_len(&[0xFF], 1).unwrap_err(), invalid_data());
+    }
+
+    // --- PROGRAM_DATA ---
+
+    #[test]
+    fn program_data_is_the_loader_pda_of_the_program() {
+        let (program_data, _) = Pubkey::find_program_address(
+            &[crate::ID.as_ref()],
+            &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        );
+        assert_eq!(program_data, PROGRAM_DATA);
+    }
+
+    // --- settle_tip ---
+
+    fn not_profitable() -> Error {
+        ArbBotError::NotProfitable.into()
+    }
+
+    #[test]
+    fn tip_is_bps_of_profit_above_the_floor() {
+        assert_eq!(settle_tip(1_000_000, AMOUNT, 0, 2_500, 1_000, 0).unwrap(), 250_000);
+        assert_eq!(settle_tip(1_000_000, AMOUNT, 0, 0, 1_000, 0).unwrap(), 1_000);
+        assert_eq!(settle_tip(1_000_000, AMOUNT, 0, 5, 1_000, 0).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn tip_is_capped_at_profit() {
+        assert_eq!(settle_tip(600, AMOUNT, 0, 100, 1_000, 0).unwrap(), 600);
+        assert_eq!(settle_tip(600, AMOUNT, 0, MAX_TIP_BPS, 0, 0).unwrap(), 600);
+        // nothing left after the capped tip
+        assert_eq!(settle_tip(600, AMOUNT, 0, 100, 1_000, 1).unwrap_err(), not_profitable());
+    }
+
+    #[test]
+    fn skip_bit_pays_no_tip() {
+        assert_eq!(settle_tip(600, AMOUNT, FLAG_SKIP_TIP, 5_000, 1_000, 600).unwrap(), 0);
+    }
+
+    #[test]
+    fn min_profit_is_net_of_the_tip() {
+        // 1_000_000 profit, 10% tip → 900_000 net
+        assert_eq!(settle_tip(1_000_000, AMOUNT, 0, 1_000, 0, 900_000).unwrap(), 100_000);
+        assert_eq!(
+            settle_tip(1_000_000, AMOUNT, 0, 1_000, 0, 900_001).unwrap_err(),
+            not_profitable()
+        );
+    }
+
+    #[test]
+    fn min_profit_in_bps_of_amount() {
+        // 9 bps of 1e9 = 900_000
+        let flags = FLAG_MIN_PROFIT_BPS;
+        assert_eq!(settle_tip(1_000_000, AMOUNT, flags, 1_000, 0, 9).unwrap(), 100_000);
+        assert_eq!(
+            settle_tip(1_000_000, AMOUNT, flags, 1_000, 0, 10).unwrap_err(),
+            not_profitable()
+        );
+        // huge bps saturate instead of overflowing
+        assert_eq!(
+            settle_tip(1_000_000, u64::MAX, flags, 0, 0, u64::MAX).unwrap_err(),
+            not_profitable()
+        );
+    }
+
+    // --- tip price conversion ---
+
+    #[test]
+    fn tokens_to_lamports_rounds_down() {
+        // 2.5 lamports per token unit
+        assert_eq!(tokens_to_lamports(4, 2_500_000_000).unwrap(), 10);
+        assert_eq!(tokens_to_lamports(3, 2_500_000_000).unwrap(), 7);
+        assert_eq!(tokens_to_lamports(1, 1).unwrap(), 0);
+        assert_eq!(
+            tokens_to_lamports(u64::MAX, 2_000_000_000).unwrap_err(),
+            ArbBotError::CalculationError.into()
+        );
+    }
+
+    #[test]
+    fn lamports_to_tokens_rounds_up() {
+        assert_eq!(lamports_to_tokens(10, 2_500_000_000).unwrap(), 4);
+        assert_eq!(lamports_to_tokens(11, 2_500_000_000).unwrap(), 5);
+        assert_eq!(lamports_to_tokens(1, u64::MAX).unwrap(), 1);
+        assert_eq!(lamports_to_tokens(0, 1).unwrap(), 0);
+        // the rounded-up amount is always worth at least the lamports
+        let tokens = lamports_to_tokens(1_000, 3_000_000_000).unwrap();
+        assert!(tokens_to_lamports(tokens, 3_000_000_000).unwrap() >= 1_000);
+        assert_eq!(
+            lamports_to_tokens(u64::MAX, 1).unwrap_err(),
+            ArbBotError::CalculationError.into()
+        );
+    }
+
+    // --- expand_hop_types ---
+
+    #[test]
+    fn expand_atomic_hops_unchanged() {
+        assert_eq!(
+            expand_hop_types(&[0x62]).unwrap(),
+            vec![HopType::RaydiumAmmV4, HopType::RaydiumCpmm]
+        );
+    }
+
+    #[test]
+    fn expand_compound_hops() {
+        // 0x01 (atomic), 0x09, then 0x0e and a pad nibble
+        assert_eq!(
+            expand_hop_types(&[0x91, 0xFE]).unwrap(),
+            vec![
+                HopType::MeteoraPools,
+                HopType::PumpfunBuy,
+                HopType::RaydiumCpammNew,
+                HopType::PancakeSwap,
+            ]
+        );
+        assert_eq!(expand_hop_types(&[0xF1]).unwrap(), vec![HopType::MeteoraPools]);
+    }
+
+    #[test]
+    fn expand_0x1a_to_unmapped_0x1b() {
+        // 0xF, 0xB → 0x1a
+        let hops = expand_hop_types(&[0xBF]).unwrap();
+        assert_eq!(hops, vec![HopType::Unmapped1b]);
+        assert!(hops[0].dex().is_err());
+    }
+
+    #[test]
+    fn expand_rejects_empty_route_and_high_types() {
+        assert_eq!(expand_hop_types(&[]).unwrap_err(), ArbBotError::InvalidHopConfig.into());
+        assert_eq!(expand_hop_types(&[0xFF]).unwrap_err(), invalid_data());
+    }
+}
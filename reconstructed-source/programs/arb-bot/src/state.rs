@@ -6,13 +6,18 @@
 //   "unpack", "extensions", "length of input"
 // This module likely defines the state/data structures used by the program.
 //
-// Since this is an arb bot that doesn't store persistent state (no PDA data
-// accounts visible in transactions), this module likely contains helpers for
-// deserializing external account state (token accounts, mint accounts, pools).
+// The deployed bot stores no persistent state (no PDA data accounts visible in
+// transactions), so most of this module is helpers for deserializing external
+// account state (token accounts, mint accounts, pools). The one account of our
+// own is `Config` (not in the original binary), which moves the tip and
+// operator settings out of compile-time constants.
 // =============================================================================
 
 use anchor_lang::prelude::*;
 
+use crate::processor::{HopType, TipMode, ALL_HOP_TYPES};
+use crate::ArbBotError;
+
 /// Minimal deserialized SPL Token account data.
 /// Used to read token balances without full spl-token dependency.
 #[derive(Clone, Debug)]
@@ -20,6 +25,8 @@ pub struct TokenAccountState {
     pub mint: Pubkey,
     pub owner: Pubkey,
     pub amount: u64,
+    /// AccountState::Frozen (byte 108 == 2)
+    pub is_frozen: bool,
 }
 
 impl TokenAccountState {
@@ -37,11 +44,13 @@ impl TokenAccountState {
         let mint = Pubkey::try_from(&data[0..32]).unwrap();
         let owner = Pubkey::try_from(&data[32..64]).unwrap();
         let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+        let is_frozen = data[108] == 2;
 
         Ok(Self {
             mint,
             owner,
             amount,
+            is_frozen,
         })
     }
 }
@@ -97,16 +106,292 @@ impl MintState {
     }
 }
 
+/// Decoded instruction data for arb_swap (instruction 0x00).
+///
+/// Produced by `processor::decode_arb_swap_data` (Ghidra: FUN_ram_0000e1c0).
+/// Layout:
+///   [0]      : instruction index (0x00)
+///   [1]      : num_hops (u8)
+///   [2..10]  : amount (u64 LE)
+///   [10..12] : flags (u16 LE)
+///   [12..N]  : nibble-packed hop types (see `processor::decode_arb_swap_data`)
+///   [N..M]   : one byte per expanded hop — variable tail account count
+///              (flags version 1 with FLAG_V1_EXTRA_ACCOUNTS; else all zero)
+///   [M..]    : optional u64 min_out per expanded hop (flags & FLAG_HOP_MIN_OUT)
+///   [..]     : optional u64 min_profit (flags & FLAG_MIN_PROFIT)
+///   [..]     : u16 tip_bps (flags version 1 only)
+///   [..]     : u64 tip price (flags version 1 with FLAG_V1_TIP_PRICED)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbSwapArgs {
+    /// Number of packed hop type entries (before compound expansion)
+    pub num_hops: u8,
+    /// Input amount of the first hop, in the smallest unit of the source token
+    pub amount: u64,
+    /// Flags word — low byte is the tip percentage (v0) or tip mode bits
+    /// (v1), bit 8 = per-hop min_out, bit 9 = min_profit present,
+    /// bit 10 = min_profit in bps, bit 11 = skip tip, bits 12..16 = layout
+    /// version
+    pub flags: u16,
+    /// Raw nibble-packed hop types, exactly as they appeared in the data
+    pub hop_types: Vec<u8>,
+    /// Per expanded hop: number of accounts after the DEX's fixed layout
+    /// (e.g. Meteora DLMM bin arrays). Zero for fixed-layout DEXes.
+    pub hop_extra_accounts: Vec<u8>,
+    /// Per expanded hop: minimum output, empty unless FLAG_HOP_MIN_OUT is set
+    pub hop_min_out: Vec<u64>,
+    /// Profit required after the tip (absolute, or bps of `amount` with
+    /// FLAG_MIN_PROFIT_BPS); 0 unless FLAG_MIN_PROFIT is set
+    pub min_profit: u64,
+    /// Tip in bps of profit (0..=10000), from the v0 percent byte or the v1 field
+    pub tip_bps: u16,
+    /// How the tip is paid (v1 low byte bits; always lamports for v0)
+    pub tip_mode: TipMode,
+}
+
 /// Represents the configuration for a single swap hop.
 ///
-/// This is likely parsed from remaining_accounts at runtime rather than
-/// stored on-chain, but the struct helps document the expected layout.
+/// Built by the swap_router for each expanded hop (after the registry and
+/// program-id checks) and handed to the DEX's CPI handler.
 #[derive(Clone, Debug)]
 pub struct HopConfig {
+    /// Expanded (atomic) hop type
+    pub hop_type: HopType,
     /// Index into remaining_accounts where this hop's accounts start
     pub account_offset: usize,
     /// Number of accounts consumed by this hop
     pub num_accounts: usize,
+    /// Of those, accounts in the variable tail (count from instruction data)
+    pub extra_accounts: u8,
     /// The DEX program to call
     pub dex_program: Pubkey,
+    /// Amount of the input token to swap (previous hop's output after the first hop)
+    pub amount_in: u64,
+    /// Minimum output the DEX must deliver (0 = no per-hop limit)
+    pub min_amount_out: u64,
+}
+
+/// Bounds-checked cursor over remaining_accounts.
+///
+/// Hops consume their accounts through `take` / `take_variable`, so a short
+/// account list surfaces as `CalculationError` (6003, "not enough
+/// remaining_accounts") instead of a slice-index panic.
+pub struct AccountCursor<'a, 'info> {
+    accounts: &'a [AccountInfo<'info>],
+    position: usize,
+}
+
+impl<'a, 'info> AccountCursor<'a, 'info> {
+    pub fn new(accounts: &'a [AccountInfo<'info>]) -> Self {
+        Self {
+            accounts,
+            position: 0,
+        }
+    }
+
+    /// Index of the next unconsumed account.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Accounts not consumed yet.
+    pub fn remaining(&self) -> &'a [AccountInfo<'info>] {
+        &self.accounts[self.position..]
+    }
+
+    /// Consumes the next `n` accounts.
+    pub fn take(&mut self, n: usize) -> Result<&'a [AccountInfo<'info>]> {
+        let taken = self
+            .remaining()
+            .get(..n)
+            .ok_or(ArbBotError::CalculationError)?;
+        self.position += n;
+        Ok(taken)
+    }
+
+    /// Consumes a variable-length tail whose length comes from instruction
+    /// data (DLMM bin arrays, CLMM tick arrays).
+    pub fn take_variable(&mut self, len_from_data: u8) -> Result<&'a [AccountInfo<'info>]> {
+        self.take(len_from_data as usize)
+    }
+}
+
+/// PDA seed of the `Config` account: `[CONFIG_SEED]` under this program.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Maximum `Config::tip_recipients` entries.
+pub const MAX_TIP_RECIPIENTS: usize = 8;
+
+/// Maximum `Config::operators` entries.
+pub const MAX_OPERATORS: usize = 8;
+
+/// Runtime settings for arb_swap, edited with update_config / set_admin.
+///
+/// Not in the original binary. arb_swap requires it, so a caller can't skip
+/// these checks by leaving the account out.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Key allowed to run update_config / set_admin
+    pub admin: Pubkey,
+    /// Tip floor in lamports (FUN_ram_00002368 hardcodes 1000)
+    pub min_tip_lamports: u64,
+    /// Bit n set = atomic hop type n may be routed through
+    pub enabled_dexes: u32,
+    /// Allowed tip recipients; empty = the Jito tip accounts
+    #[max_len(MAX_TIP_RECIPIENTS)]
+    pub tip_recipients: Vec<Pubkey>,
+    /// Keys allowed to run arb_swap besides the upgrade authority
+    #[max_len(MAX_OPERATORS)]
+    pub operators: Vec<Pubkey>,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Config {
+    /// Overwrites every setting except `admin` and `bump`.
+    pub fn apply(&mut self, params: ConfigParams) {
+        self.min_tip_lamports = params.min_tip_lamports;
+        self.enabled_dexes = params.enabled_dexes;
+        self.tip_recipients = params.tip_recipients;
+        self.operators = params.operators;
+    }
+}
+
+/// Instruction data for initialize_config (0x01) and update_config (0x03),
+/// Borsh-encoded after the instruction index byte.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigParams {
+    pub min_tip_lamports: u64,
+    pub enabled_dexes: u32,
+    pub tip_recipients: Vec<Pubkey>,
+    pub operators: Vec<Pubkey>,
+}
+
+impl ConfigParams {
+    /// Decodes `data[1..]`. Malformed or trailing bytes are
+    /// `InvalidInstructionData`; lists over their maximum and bits above hop
+    /// type 0x1a are `InvalidConfig`.
+    pub fn unpack(data: &[u8]) -> Result<Self> {
+        let params = data
+            .get(1..)
+            .and_then(|mut rest| {
+                let params = Self::deserialize(&mut rest).ok()?;
+                rest.is_empty().then_some(params)
+            })
+            .ok_or(ArbBotError::InvalidInstructionData)?;
+
+        if params.tip_recipients.len() > MAX_TIP_RECIPIENTS
+            || params.operators.len() > MAX_OPERATORS
+            || params.enabled_dexes & !ALL_HOP_TYPES != 0
+        {
+            return Err(ArbBotError::InvalidConfig.into());
+        }
+
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- AccountCursor ---
+
+    #[test]
+    fn cursor_take_is_bounds_checked() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let owner = Pubkey::new_unique();
+        let mut lamports = [0u64; 3];
+        let mut data = [[0u8; 0]; 3];
+        let accounts: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, &owner, false, 0)
+            })
+            .collect();
+
+        let mut cursor = AccountCursor::new(&accounts);
+        let taken = cursor.take(2).unwrap();
+        assert_eq!(taken.iter().map(|a| *a.key).collect::<Vec<_>>(), keys[..2]);
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.remaining().len(), 1);
+
+        // A short list fails without moving the cursor
+        assert_eq!(
+            cursor.take_variable(2).unwrap_err(),
+            ArbBotError::CalculationError.into()
+        );
+        assert_eq!(cursor.position(), 2);
+
+        assert_eq!(*cursor.take(1).unwrap()[0].key, keys[2]);
+        assert!(cursor.take(0).unwrap().is_empty());
+        assert_eq!(cursor.take(1).unwrap_err(), ArbBotError::CalculationError.into());
+    }
+
+    // --- ConfigParams::unpack ---
+
+    fn config_params() -> ConfigParams {
+        ConfigParams {
+            min_tip_lamports: 1_000,
+            enabled_dexes: ALL_HOP_TYPES,
+            tip_recipients: vec![Pubkey::new_unique()],
+            operators: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        }
+    }
+
+    /// Instruction data for `params` (index byte 0x01 + Borsh).
+    fn config_data(params: &ConfigParams) -> Vec<u8> {
+        let mut data = vec![crate::IX_INITIALIZE_CONFIG];
+        params.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn config_params_round_trip() {
+        let params = config_params();
+        assert_eq!(ConfigParams::unpack(&config_data(&params)).unwrap(), params);
+    }
+
+    #[test]
+    fn config_params_rejects_malformed_data() {
+        let data = config_data(&config_params());
+        for len in 0..data.len() {
+            assert_eq!(
+                ConfigParams::unpack(&data[..len]).unwrap_err(),
+                ArbBotError::InvalidInstructionData.into()
+            );
+        }
+
+        let mut trailing = data;
+        trailing.push(0);
+        assert_eq!(
+            ConfigParams::unpack(&trailing).unwrap_err(),
+            ArbBotError::InvalidInstructionData.into()
+        );
+    }
+
+    #[test]
+    fn config_params_rejects_out_of_range_settings() {
+        let too_many_operators = ConfigParams {
+            operators: vec![Pubkey::new_unique(); MAX_OPERATORS + 1],
+            ..config_params()
+        };
+        let too_many_recipients = ConfigParams {
+            tip_recipients: vec![Pubkey::new_unique(); MAX_TIP_RECIPIENTS + 1],
+            ..config_params()
+        };
+        let unknown_hop_type = ConfigParams {
+            enabled_dexes: ALL_HOP_TYPES + 1,
+            ..config_params()
+        };
+
+        for params in [too_many_operators, too_many_recipients, unknown_hop_type] {
+            assert_eq!(
+                ConfigParams::unpack(&config_data(&params)).unwrap_err(),
+                ArbBotError::InvalidConfig.into()
+            );
+        }
+    }
 }